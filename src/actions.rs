@@ -0,0 +1,175 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+
+macro_rules! action_results {
+    ($($(#[doc = $doc:literal])* $variant:ident = $value:literal,)*) => {
+        /// The status Smile ID reports for a single check.
+        ///
+        /// Statuses the crate does not know yet are kept as `Unknown`.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum ActionResult {
+            $($(#[doc = $doc])* $variant,)*
+            Unknown(String),
+        }
+
+        impl ActionResult {
+            /// The status as Smile ID sends it, e.g. `"Not Applicable"`.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(ActionResult::$variant => $value,)*
+                    ActionResult::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for ActionResult {
+            fn from(value: &str) -> Self {
+                match value.trim() {
+                    $($value => ActionResult::$variant,)*
+                    value => ActionResult::Unknown(value.to_string()),
+                }
+            }
+        }
+    };
+}
+
+action_results! {
+    Passed = "Passed",
+    Failed = "Failed",
+    Completed = "Completed",
+    Approved = "Approved",
+    Rejected = "Rejected",
+    Verified = "Verified",
+    Unverified = "Unverified",
+    Returned = "Returned",
+    NotReturned = "Not Returned",
+    ExactMatch = "Exact Match",
+    PartialMatch = "Partial Match",
+    TransliteratedMatch = "Transliterated Match",
+    ProvisionalApproval = "Provisional Approval",
+    UnderReview = "Under Review",
+    /// The check was skipped for this job.
+    NotDone = "Not Done",
+    /// The check does not apply to this product.
+    NotApplicable = "Not Applicable",
+}
+
+impl ActionResult {
+    /// Whether the check ran and did not pass.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, ActionResult::Failed | ActionResult::Rejected | ActionResult::Unverified)
+    }
+}
+
+impl fmt::Display for ActionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ActionResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ActionResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|value| ActionResult::from(value.as_str()))
+    }
+}
+
+macro_rules! actions {
+    ($($(#[doc = $doc:literal])* $field:ident = $name:literal,)*) => {
+        /// The checks Smile ID ran for a job and how each one ended.
+        ///
+        /// Checks without a field of their own are kept in `other`, keyed by
+        /// the name Smile ID uses.
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+        pub struct Actions {
+            $(
+                $(#[doc = $doc])*
+                #[serde(rename = $name, default, skip_serializing_if = "Option::is_none")]
+                pub $field: Option<ActionResult>,
+            )*
+            #[serde(flatten)]
+            pub other: HashMap<String, ActionResult>,
+        }
+
+        impl Actions {
+            /// Every reported check with its Smile ID name.
+            pub fn iter(&self) -> impl Iterator<Item = (&str, &ActionResult)> {
+                [$(($name, self.$field.as_ref()),)*]
+                    .into_iter()
+                    .filter_map(|(name, result)| Some((name, result?)))
+                    .chain(self.other.iter().map(|(name, result)| (name.as_str(), result)))
+            }
+        }
+    };
+}
+
+actions! {
+    liveness_check = "Liveness_Check",
+    register_selfie = "Register_Selfie",
+    selfie_provided = "Selfie_Provided",
+    selfie_check = "Selfie_Check",
+    verify_id_number = "Verify_ID_Number",
+    verify_document = "Verify_Document",
+    document_check = "Document_Check",
+    return_personal_info = "Return_Personal_Info",
+    selfie_to_id_card_compare = "Selfie_To_ID_Card_Compare",
+    selfie_to_id_authority_compare = "Selfie_To_ID_Authority_Compare",
+    selfie_to_registered_selfie_compare = "Selfie_To_Registered_Selfie_Compare",
+    human_review_compare = "Human_Review_Compare",
+    human_review_liveness_check = "Human_Review_Liveness_Check",
+    human_review_document_check = "Human_Review_Document_Check",
+    update_registered_selfie_on_file = "Update_Registered_Selfie_On_File",
+}
+
+impl Actions {
+    /// The result of the check Smile ID calls `name`, e.g. `Liveness_Check`.
+    pub fn get(&self, name: &str) -> Option<&ActionResult> {
+        self.iter().find(|(check, _)| *check == name).map(|(_, result)| result)
+    }
+
+    /// The checks that ran and did not pass.
+    pub fn failed(&self) -> impl Iterator<Item = (&str, &ActionResult)> {
+        self.iter().filter(|(_, result)| result.is_failure())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_named_and_unlisted_checks() {
+        let actions: Actions = serde_json::from_str(
+            r#"{
+                "Liveness_Check": "Passed",
+                "Selfie_To_ID_Card_Compare": "Failed",
+                "Human_Review_Compare": "Not Applicable",
+                "Verify_ID_Number": "Not Done",
+                "Future_Check": "Unverified"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(actions.liveness_check, Some(ActionResult::Passed));
+        assert_eq!(actions.human_review_compare, Some(ActionResult::NotApplicable));
+        assert_eq!(actions.get("Future_Check"), Some(&ActionResult::Unverified));
+
+        let mut failed: Vec<_> = actions.failed().map(|(name, _)| name).collect();
+        failed.sort();
+        assert_eq!(failed, ["Future_Check", "Selfie_To_ID_Card_Compare"]);
+    }
+
+    #[test]
+    fn unknown_statuses_round_trip() {
+        let actions: Actions = serde_json::from_str(r#"{"Liveness_Check": "Inconclusive"}"#).unwrap();
+
+        assert_eq!(actions.liveness_check, Some(ActionResult::Unknown("Inconclusive".to_string())));
+        assert_eq!(serde_json::to_string(&actions).unwrap(), r#"{"Liveness_Check":"Inconclusive"}"#);
+    }
+}
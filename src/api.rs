@@ -1,35 +1,54 @@
-use chrono::Utc;
-use reqwest::{Client, ClientBuilder};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::time::Duration;
+use std::sync::Arc;
 
-use crate::auth::Auth;
-use crate::config::Config;
-use crate::error::{Error, Result};
-use crate::models::{ApiResponse, JobStatusRequest, JobStatusResponse};
+use crate::circuit_breaker::CircuitBreaker;
+use crate::config::{Config, Environment};
+use crate::core::{Attempt, Call, Core};
+use crate::error::Result;
+use crate::interceptor::Interceptor;
+use crate::models::{JobStatusRequest, JobStatusResponse};
+use crate::options::{cancellable, RequestOptions};
+use crate::rate_limit::RateLimiter;
+use crate::services::{Lookup, Services};
+use crate::telemetry::RequestSpan;
+use crate::transport::{HttpRequest, ReqwestTransport, Transport};
+use crate::utils;
 
 #[derive(Debug, Clone)]
 pub struct ApiClient {
-    client: Client,
-    auth: Auth,
-    config: Config,
+    transport: Arc<dyn Transport>,
+    core: Core,
 }
 
 impl ApiClient {
     pub fn new(config: Config) -> Result<Self> {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(config.timeout))
-            .build()
-            .map_err(Error::Http)?;
+        let transport = ReqwestTransport::from_config(&config)?;
 
-        let auth = Auth::new(&config.api_key, &config.partner_id);
+        Ok(Self::with_transport(config, transport))
+    }
+
+    /// Creates a client that sends every request through `transport`
+    /// instead of the default reqwest client.
+    pub fn with_transport(config: Config, transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            core: Core::new(config),
+        }
+    }
+
+    /// Creates a client that shares `transport`, and its connection pool, with other clients.
+    pub(crate) fn with_shared_transport(config: Config, transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            core: Core::new(config),
+        }
+    }
 
-        Ok(Self {
-            client,
-            auth,
-            config,
-        })
+    /// Adds an interceptor that runs around every request sent by this client.
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.core.add_interceptor(Arc::new(interceptor));
+        self
     }
 
     pub async fn get_job_status(
@@ -39,15 +58,26 @@ impl ApiClient {
         include_history: Option<bool>,
         include_image_links: Option<bool>,
     ) -> Result<JobStatusResponse> {
-        let request = JobStatusRequest {
-            user_id: user_id.into(),
-            job_id: job_id.into(),
+        self.get_job_status_with_options(
+            user_id,
+            job_id,
             include_history,
             include_image_links,
-        };
+            &RequestOptions::default(),
+        )
+        .await
+    }
 
-        let url = format!("{}/job_status", self.base_url());
-        self.post(&url, &request).await
+    pub async fn get_job_status_with_options(
+        &self,
+        user_id: impl Into<String>,
+        job_id: impl Into<String>,
+        include_history: Option<bool>,
+        include_image_links: Option<bool>,
+        options: &RequestOptions,
+    ) -> Result<JobStatusResponse> {
+        let call = job_status(user_id, job_id, include_history, include_image_links);
+        self.execute(call, options).await
     }
 
     pub async fn post<T, R>(&self, url: &str, payload: &T) -> Result<R>
@@ -55,74 +85,180 @@ impl ApiClient {
         T: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        let timestamp = Utc::now();
-        let json = serde_json::to_string(payload).map_err(Error::Json)?;
-        let signature = self.auth.generate_signature(&timestamp, &json)?;
-
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .header("X-Smile-Partner-ID", self.auth.partner_id())
-            .header("X-Smile-Signature", &signature)
-            .header("X-Smile-Timestamp", timestamp.to_rfc3339())
-            .body(json)
-            .send()
+        self.post_with_options(url, payload, &RequestOptions::default()).await
+    }
+
+    /// Like [`post`](Self::post), with `options` overriding the client defaults for this call.
+    pub async fn post_with_options<T, R>(&self, url: &str, payload: &T, options: &RequestOptions) -> Result<R>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        self.send_post(url, payload, false, options).await
+    }
+
+    async fn send_post<T, R>(&self, url: &str, payload: &T, idempotent: bool, options: &RequestOptions) -> Result<R>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let span = self.core.span(url);
+
+        self.call(|number| self.core.prepare(url, payload, options, number), idempotent, options, &span)
             .await
-            .map_err(Error::Http)?;
+            .map_err(|e| self.core.failed(e, &span))
+    }
 
-        let status = response.status();
-        let body = response.text().await.map_err(Error::Http)?;
+    /// Fetches the markets, ID types and products Smile ID currently supports.
+    ///
+    /// The response is reused until it expires when `Config::with_services_cache` is set.
+    pub async fn services(&self) -> Result<Services> {
+        self.services_with_options(&RequestOptions::default()).await
+    }
 
-        if !status.is_success() {
-            return Err(Error::Api {
-                status_code: status.as_u16(),
-                message: body,
-            });
+    pub async fn services_with_options(&self, options: &RequestOptions) -> Result<Services> {
+        let Some(cache) = self.core.services_cache() else {
+            return self.fetch_services(options).await;
+        };
+
+        loop {
+            match cache.lookup() {
+                Lookup::Cached(services) => return Ok(services),
+                Lookup::Wait => cancellable(cache.refreshed(), options).await?,
+                Lookup::Refresh(refresh) => {
+                    let services = self.fetch_services(options).await?;
+                    refresh.finish(&services);
+                    return Ok(services);
+                }
+            }
         }
+    }
+
+    /// Fetches `/services` with the same rate limiting, retries and circuit
+    /// breaker as every other call.
+    async fn fetch_services(&self, options: &RequestOptions) -> Result<Services> {
+        let span = self.core.span(&self.core.services_url());
 
-        let api_response: ApiResponse<R> = serde_json::from_str(&body).map_err(Error::Json)?;
+        self.call(|number| self.core.prepare_services(options, number), true, options, &span)
+            .await
+            .map_err(|e| self.core.failed(e, &span))
+    }
 
-        if api_response.status_code >= 400 {
-            return Err(Error::Api {
-                status_code: api_response.status_code,
-                message: api_response.message,
-            });
+    pub(crate) async fn execute<P, R>(&self, call: Call<P>, options: &RequestOptions) -> Result<R>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let url = self.core.url(&call);
+        self.send_post(&url, &call.payload, call.idempotent, options).await
+    }
+
+    /// Sends the requests `prepare` builds, retrying failed attempts. Requests
+    /// that are not `idempotent` are only resent when they cannot have been processed.
+    async fn call<F, R>(&self, prepare: F, idempotent: bool, options: &RequestOptions, span: &RequestSpan) -> Result<R>
+    where
+        F: Fn(u32) -> Result<(HttpRequest, Attempt)>,
+        R: DeserializeOwned,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match self.send(&prepare, options, span, attempt).await {
+                Ok(data) => return Ok(data),
+                Err(e) => {
+                    let delay = self.core.retry_delay(e, attempt, idempotent, options)?;
+                    cancellable(utils::sleep(delay), options).await?;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn send<F, R>(&self, prepare: &F, options: &RequestOptions, span: &RequestSpan, attempt: u32) -> Result<R>
+    where
+        F: Fn(u32) -> Result<(HttpRequest, Attempt)>,
+        R: DeserializeOwned,
+    {
+        if let Some(rate_limiter) = self.core.rate_limiter() {
+            cancellable(rate_limiter.acquire(), options).await?;
         }
+        let permit = self.core.check(options)?;
 
-        Ok(api_response.data)
+        let (request, attempt) = span.in_scope(|| prepare(attempt))?;
+        span.record_request(&request);
+        let response = cancellable(span.instrument(self.transport.send(request)), options).await?;
+
+        span.in_scope(|| self.core.complete(attempt, permit, response, span))
     }
 
     pub fn base_url(&self) -> String {
-        format!("{}/v{}", self.config.base_url, self.config.version)
+        self.core.base_url()
+    }
+
+    /// The environment every request from this client is sent to.
+    pub fn environment(&self) -> &Environment {
+        self.core.environment()
+    }
+
+    /// The limiter shared by this client and its clones, if one is configured.
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.core.rate_limiter()
+    }
+
+    /// The circuit breaker shared by this client and its clones, if one is configured.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.core.circuit_breaker()
+    }
+}
+
+fn job_status(
+    user_id: impl Into<String>,
+    job_id: impl Into<String>,
+    include_history: Option<bool>,
+    include_image_links: Option<bool>,
+) -> Call<JobStatusRequest> {
+    Call {
+        path: "job_status",
+        idempotent: true,
+        payload: JobStatusRequest {
+            user_id: user_id.into(),
+            job_id: job_id.into(),
+            include_history,
+            include_image_links,
+        },
     }
 }
 
 #[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
+    use crate::options::sleep_blocking;
+    use crate::transport::blocking::{ReqwestTransport, Transport};
 
+    /// Blocking counterpart of [`ApiClient`](super::ApiClient).
     #[derive(Debug, Clone)]
     pub struct ApiClient {
-        client: reqwest::blocking::Client,
-        auth: Auth,
-        config: Config,
+        transport: Arc<dyn Transport>,
+        core: Core,
     }
 
     impl ApiClient {
         pub fn new(config: Config) -> Result<Self> {
-            let client = reqwest::blocking::ClientBuilder::new()
-                .timeout(Duration::from_secs(config.timeout))
-                .build()
-                .map_err(Error::Http)?;
+            let transport = ReqwestTransport::from_config(&config)?;
 
-            let auth = Auth::new(&config.api_key, &config.partner_id);
+            Ok(Self::with_transport(config, transport))
+        }
 
-            Ok(Self {
-                client,
-                auth,
-                config,
-            })
+        pub fn with_transport(config: Config, transport: impl Transport + 'static) -> Self {
+            Self {
+                transport: Arc::new(transport),
+                core: Core::new(config),
+            }
+        }
+
+        pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+            self.core.add_interceptor(Arc::new(interceptor));
+            self
         }
 
         pub fn get_job_status(
@@ -132,15 +268,25 @@ pub mod blocking {
             include_history: Option<bool>,
             include_image_links: Option<bool>,
         ) -> Result<JobStatusResponse> {
-            let request = JobStatusRequest {
-                user_id: user_id.into(),
-                job_id: job_id.into(),
+            self.get_job_status_with_options(
+                user_id,
+                job_id,
                 include_history,
                 include_image_links,
-            };
+                &RequestOptions::default(),
+            )
+        }
 
-            let url = format!("{}/job_status", self.base_url());
-            self.post(&url, &request)
+        pub fn get_job_status_with_options(
+            &self,
+            user_id: impl Into<String>,
+            job_id: impl Into<String>,
+            include_history: Option<bool>,
+            include_image_links: Option<bool>,
+            options: &RequestOptions,
+        ) -> Result<JobStatusResponse> {
+            let call = job_status(user_id, job_id, include_history, include_image_links);
+            self.execute(call, options)
         }
 
         pub fn post<T, R>(&self, url: &str, payload: &T) -> Result<R>
@@ -148,45 +294,505 @@ pub mod blocking {
             T: Serialize + ?Sized,
             R: DeserializeOwned,
         {
-            let timestamp = Utc::now();
-            let json = serde_json::to_string(payload).map_err(Error::Json)?;
-            let signature = self.auth.generate_signature(&timestamp, &json)?;
-
-            let response = self
-                .client
-                .post(url)
-                .header("Content-Type", "application/json")
-                .header("X-Smile-Partner-ID", self.auth.partner_id())
-                .header("X-Smile-Signature", &signature)
-                .header("X-Smile-Timestamp", timestamp.to_rfc3339())
-                .body(json)
-                .send()
-                .map_err(Error::Http)?;
-
-            let status = response.status();
-            let body = response.text().map_err(Error::Http)?;
-
-            if !status.is_success() {
-                return Err(Error::Api {
-                    status_code: status.as_u16(),
-                    message: body,
-                });
+            self.post_with_options(url, payload, &RequestOptions::default())
+        }
+
+        pub fn post_with_options<T, R>(&self, url: &str, payload: &T, options: &RequestOptions) -> Result<R>
+        where
+            T: Serialize + ?Sized,
+            R: DeserializeOwned,
+        {
+            self.send_post(url, payload, false, options)
+        }
+
+        fn send_post<T, R>(&self, url: &str, payload: &T, idempotent: bool, options: &RequestOptions) -> Result<R>
+        where
+            T: Serialize + ?Sized,
+            R: DeserializeOwned,
+        {
+            let span = self.core.span(url);
+
+            self.call(|number| self.core.prepare(url, payload, options, number), idempotent, options, &span)
+                .map_err(|e| self.core.failed(e, &span))
+        }
+
+        pub fn services(&self) -> Result<Services> {
+            self.services_with_options(&RequestOptions::default())
+        }
+
+        pub fn services_with_options(&self, options: &RequestOptions) -> Result<Services> {
+            let Some(cache) = self.core.services_cache() else {
+                return self.fetch_services(options);
+            };
+
+            loop {
+                match cache.lookup() {
+                    Lookup::Cached(services) => return Ok(services),
+                    Lookup::Wait => cache.wait_blocking(),
+                    Lookup::Refresh(refresh) => {
+                        let services = self.fetch_services(options)?;
+                        refresh.finish(&services);
+                        return Ok(services);
+                    }
+                }
             }
+        }
+
+        fn fetch_services(&self, options: &RequestOptions) -> Result<Services> {
+            let span = self.core.span(&self.core.services_url());
 
-            let api_response: ApiResponse<R> = serde_json::from_str(&body).map_err(Error::Json)?;
+            self.call(|number| self.core.prepare_services(options, number), true, options, &span)
+                .map_err(|e| self.core.failed(e, &span))
+        }
 
-            if api_response.status_code >= 400 {
-                return Err(Error::Api {
-                    status_code: api_response.status_code,
-                    message: api_response.message,
-                });
+        pub(crate) fn execute<P, R>(&self, call: Call<P>, options: &RequestOptions) -> Result<R>
+        where
+            P: Serialize,
+            R: DeserializeOwned,
+        {
+            let url = self.core.url(&call);
+            self.send_post(&url, &call.payload, call.idempotent, options)
+        }
+
+        fn call<F, R>(&self, prepare: F, idempotent: bool, options: &RequestOptions, span: &RequestSpan) -> Result<R>
+        where
+            F: Fn(u32) -> Result<(HttpRequest, Attempt)>,
+            R: DeserializeOwned,
+        {
+            let mut attempt = 1;
+
+            loop {
+                match self.send(&prepare, options, span, attempt) {
+                    Ok(data) => return Ok(data),
+                    Err(e) => {
+                        let delay = self.core.retry_delay(e, attempt, idempotent, options)?;
+                        sleep_blocking(delay, options)?;
+                        attempt += 1;
+                    }
+                }
+            }
+        }
+
+        fn send<F, R>(&self, prepare: &F, options: &RequestOptions, span: &RequestSpan, attempt: u32) -> Result<R>
+        where
+            F: Fn(u32) -> Result<(HttpRequest, Attempt)>,
+            R: DeserializeOwned,
+        {
+            if let Some(rate_limiter) = self.core.rate_limiter() {
+                while let Err(wait) = rate_limiter.try_acquire() {
+                    sleep_blocking(wait, options)?;
+                }
             }
+            let permit = self.core.check(options)?;
 
-            Ok(api_response.data)
+            span.in_scope(|| {
+                let (request, attempt) = prepare(attempt)?;
+                span.record_request(&request);
+                let response = self.transport.send(request);
+
+                self.core.complete(attempt, permit, response, span)
+            })
         }
 
         pub fn base_url(&self) -> String {
-            format!("{}/v{}", self.config.base_url, self.config.version)
+            self.core.base_url()
+        }
+
+        pub fn environment(&self) -> &Environment {
+            self.core.environment()
+        }
+
+        pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+            self.core.rate_limiter()
+        }
+
+        pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+            self.core.circuit_breaker()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::ActionResult;
+    use crate::auth::Auth;
+    use crate::circuit_breaker::CircuitBreakerConfig;
+    use crate::credentials::RotatingCredentials;
+    use crate::date_of_birth::{DateOfBirth, ReturnedDateOfBirth};
+    use crate::error::Error;
+    use crate::options::CancellationToken;
+    use crate::rate_limit::RateLimit;
+    use crate::retry::RetryPolicy;
+    use crate::transport::testing::FakeTransport;
+    use crate::transport::{HttpRequest, HttpResponse, Method};
+    use chrono::{NaiveDate, Utc};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    const JOB_STATUS_BODY: &str = include_str!("../tests/fixtures/job_status.json");
+
+    fn retrying_config(max_attempts: u32) -> Config {
+        Config::new("key", "partner").with_retry_policy(
+            RetryPolicy::default()
+                .with_max_attempts(max_attempts)
+                .with_base_delay(Duration::from_millis(1)),
+        )
+    }
+
+    #[test]
+    fn requests_are_signed_and_sent_through_the_transport() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY);
+        let client = ApiClient::with_transport(Config::new("key", "partner"), transport.clone());
+
+        let status = tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap();
+
+        assert!(status.job_complete);
+        assert_eq!(status.result.unwrap().smile_job_id.as_deref(), Some("0000000321"));
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://testapi.smileidentity.com/v1/job_status");
+        assert!(requests[0]
+            .headers
+            .iter()
+            .any(|(name, value)| name == "SmileID-Partner-ID" && value == "partner"));
+    }
+
+    #[test]
+    fn retryable_failures_are_retried_until_success() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY)
+            .queue(503, "unavailable")
+            .queue(502, "bad gateway");
+        let client = ApiClient::with_transport(retrying_config(3), transport.clone());
+
+        let status = tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap();
+
+        assert!(status.job_success);
+        assert_eq!(transport.requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn final_error_reports_the_number_of_attempts() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY)
+            .queue(503, "unavailable")
+            .queue(503, "unavailable");
+        let client = ApiClient::with_transport(retrying_config(2), transport.clone());
+
+        let err = tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap_err();
+
+        assert_eq!(err.environment(), Some(&Environment::Sandbox));
+        match err {
+            Error::RetriesExhausted { attempts, source } => {
+                assert_eq!(attempts, 2);
+                assert!(matches!(*source, Error::Api { status_code: 503, .. }));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn open_circuit_fails_fast_without_sending() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY).queue(503, "unavailable");
+        let config = Config::new("key", "partner")
+            .with_circuit_breaker(CircuitBreakerConfig::new(1, Duration::from_secs(60)));
+        let client = ApiClient::with_transport(config, transport.clone());
+
+        let first = tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None));
+        let second = tokio_test::block_on(client.clone().get_job_status("user-1", "job-1", None, None));
+
+        assert!(matches!(first.unwrap_err(), Error::Api { status_code: 503, .. }));
+        assert!(matches!(second.unwrap_err(), Error::CircuitOpen { .. }));
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+    }
+
+    /// Cancels the call once the given attempt's response has arrived, so the
+    /// cancellation lands during that attempt's backoff.
+    #[derive(Debug)]
+    struct CancelAfter {
+        attempt: usize,
+        token: CancellationToken,
+        received: Mutex<usize>,
+    }
+
+    impl Interceptor for Arc<CancelAfter> {
+        fn after_receive(&self, _request: &HttpRequest, _response: &Result<HttpResponse>) {
+            let mut received = self.received.lock().unwrap();
+            *received += 1;
+            if *received == self.attempt {
+                self.token.cancel();
+            }
+        }
+    }
+
+    #[test]
+    fn cancelling_during_a_backoff_returns_cancelled() {
+        let transport = FakeTransport::replying(503, "unavailable");
+        let token = CancellationToken::new();
+        let interceptor = Arc::new(CancelAfter {
+            attempt: 2,
+            token: token.clone(),
+            received: Mutex::new(0),
+        });
+        let config = Config::new("key", "partner").with_retry_policy(
+            RetryPolicy::default()
+                .with_max_attempts(5)
+                .with_base_delay(Duration::from_millis(50))
+                .with_jitter(false),
+        );
+        let client = ApiClient::with_transport(config, transport.clone()).with_interceptor(interceptor);
+        let options = RequestOptions::new().with_cancellation(token);
+
+        let err = tokio_test::block_on(client.get_job_status_with_options("user-1", "job-1", None, None, &options))
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Cancelled), "{err:?}");
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn a_circuit_opening_between_attempts_is_not_reported_as_exhausted() {
+        let transport = FakeTransport::replying(503, "unavailable");
+        let config = retrying_config(5).with_circuit_breaker(CircuitBreakerConfig::new(2, Duration::from_secs(60)));
+        let client = ApiClient::with_transport(config, transport.clone());
+
+        let err = tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap_err();
+
+        assert!(matches!(err, Error::CircuitOpen { .. }), "{err:?}");
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+
+    const VERIFY_BODY: &str = include_str!("../tests/fixtures/basic_kyc.json");
+    const ENHANCED_KYC_BODY: &str = include_str!("../tests/fixtures/enhanced_kyc.json");
+
+    #[test]
+    fn product_calls_return_the_smile_job_id() {
+        let transport = FakeTransport::new(VERIFY_BODY);
+        let client = ApiClient::with_transport(Config::new("key", "partner"), transport.clone());
+
+        let response =
+            tokio_test::block_on(crate::products::BasicKyc::new(client).verify("NIN", "12345678901", "NG", None, None, None))
+                .unwrap();
+
+        assert_eq!(response.smile_job_id.as_deref(), Some("0000000321"));
+        assert_eq!(
+            transport.requests.lock().unwrap()[0].url,
+            "https://testapi.smileidentity.com/v1/basic_kyc"
+        );
+    }
+
+    #[test]
+    fn submissions_are_not_resent_after_a_server_error_unless_opted_in() {
+        let transport = FakeTransport::new(VERIFY_BODY).queue(503, "unavailable");
+        let client = ApiClient::with_transport(retrying_config(3), transport.clone());
+
+        let err = tokio_test::block_on(crate::products::BasicKyc::new(client).verify("NIN", "12345678901", "NG", None, None, None))
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Api { status_code: 503, .. }));
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+
+        let transport = FakeTransport::new(VERIFY_BODY).queue(503, "unavailable");
+        let config = Config::new("key", "partner").with_retry_policy(
+            RetryPolicy::default()
+                .with_base_delay(Duration::from_millis(1))
+                .with_submission_retries(true),
+        );
+        let client = ApiClient::with_transport(config, transport.clone());
+
+        tokio_test::block_on(crate::products::BasicKyc::new(client).verify("NIN", "12345678901", "NG", None, None, None))
+            .unwrap();
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn enhanced_kyc_returns_the_result_and_identity_fields() {
+        let client = ApiClient::with_transport(Config::new("key", "partner"), FakeTransport::new(ENHANCED_KYC_BODY));
+
+        let response = tokio_test::block_on(crate::products::EnhancedKyc::new(client).verify(
+            "NIN", "12345678901", "NG", "Jane", "Doe", NaiveDate::from_ymd_opt(1990, 1, 1).unwrap(),
+        ))
+        .unwrap();
+
+        assert_eq!(response.full_name.as_deref(), Some("DOE JANE"));
+        assert_eq!(
+            response.dob.as_ref().and_then(ReturnedDateOfBirth::parsed),
+            Some(&DateOfBirth::new(1990, 1, 1).unwrap())
+        );
+        assert_eq!(response.submission.job_id(), Some("job-1"));
+        assert_eq!(response.submission.smile_job_id.as_deref(), Some("0000000321"));
+        assert_eq!(response.submission.result_code.as_ref().map(|code| code.as_str()), Some("1012"));
+        assert_eq!(response.submission.actions.get("Verify_ID_Number"), Some(&ActionResult::Verified));
+        assert_eq!(response.submission.fields["Country"], "NG");
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_clients_send_the_same_requests() {
+        let transport = FakeTransport::new(VERIFY_BODY).queue(503, "unavailable");
+        let mut config = retrying_config(2);
+        config.retry.retry_submissions = true;
+        let client = blocking::ApiClient::with_transport(config, transport.clone());
+        let options = RequestOptions::new().with_idempotency_key("idem-1");
+
+        let response = crate::products::blocking::BasicKyc::new(client)
+            .verify_with_options("NIN", "12345678901", "NG", None, None, None, &options)
+            .unwrap();
+
+        assert_eq!(response.smile_job_id.as_deref(), Some("0000000321"));
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].url, "https://testapi.smileidentity.com/v1/basic_kyc");
+        assert!(requests[1]
+            .headers
+            .contains(&("Idempotency-Key".to_string(), "idem-1".to_string())));
+    }
+
+    /// Tags every request and rewrites the payload, then records response statuses.
+    #[derive(Debug, Default)]
+    struct AuditInterceptor {
+        statuses: Mutex<Vec<u16>>,
+    }
+
+    impl Interceptor for Arc<AuditInterceptor> {
+        fn before_send(&self, request: &mut HttpRequest) -> Result<()> {
+            request.headers.push(("X-Correlation-ID".to_string(), "corr-1".to_string()));
+            request.body = request.body.replace("user-1", "user-2");
+            Ok(())
+        }
+
+        fn after_receive(&self, _request: &HttpRequest, response: &Result<HttpResponse>) {
+            if let Ok(response) = response {
+                self.statuses.lock().unwrap().push(response.status);
+            }
+        }
+    }
+
+    #[test]
+    fn interceptors_run_around_every_attempt_before_signing() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY).queue(503, "unavailable");
+        let interceptor = Arc::new(AuditInterceptor::default());
+        let client = ApiClient::with_transport(retrying_config(2), transport.clone())
+            .with_interceptor(interceptor.clone());
+
+        tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap();
+
+        assert_eq!(*interceptor.statuses.lock().unwrap(), vec![503, 200]);
+        let requests = transport.requests.lock().unwrap();
+        let header = |name: &str| {
+            requests[1]
+                .headers
+                .iter()
+                .find(|(header, _)| header == name)
+                .map(|(_, value)| value.clone())
+                .unwrap()
+        };
+        assert_eq!(header("X-Correlation-ID"), "corr-1");
+
+        let body: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(body["user_id"], "user-2");
+        assert_eq!(body["partner_id"], "partner");
+        assert_eq!(body["signature"].as_str(), Some(header("SmileID-Request-Signature").as_str()));
+
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&header("SmileID-Timestamp"))
+            .unwrap()
+            .with_timezone(&Utc);
+        let auth = Auth::new("key", "partner");
+        assert!(auth
+            .verify_signature(&header("SmileID-Request-Signature"), &timestamp)
+            .unwrap());
+    }
+
+    #[test]
+    fn request_options_override_client_defaults() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY).queue(503, "unavailable");
+        let client = ApiClient::with_transport(Config::new("key", "partner"), transport.clone());
+        let options = RequestOptions::new()
+            .with_timeout(Duration::from_secs(120))
+            .with_header("X-Tenant", "tenant-1")
+            .with_idempotency_key("idem-1")
+            .with_retry_policy(RetryPolicy::default().with_max_attempts(2).with_base_delay(Duration::from_millis(1)));
+
+        tokio_test::block_on(client.get_job_status_with_options("user-1", "job-1", None, None, &options)).unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|request| {
+            request.timeout == Some(Duration::from_secs(120))
+                && request.headers.contains(&("X-Tenant".to_string(), "tenant-1".to_string()))
+                && request.headers.contains(&("Idempotency-Key".to_string(), "idem-1".to_string()))
+        }));
+    }
+
+    #[test]
+    fn cancelled_calls_are_not_sent() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY);
+        let client = ApiClient::with_transport(Config::new("key", "partner"), transport.clone());
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let err = tokio_test::block_on(client.get_job_status_with_options(
+            "user-1",
+            "job-1",
+            None,
+            None,
+            &RequestOptions::new().with_cancellation(token),
+        ))
+        .unwrap_err();
+
+        assert!(matches!(err, Error::Cancelled));
+        assert!(transport.requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn rotated_credentials_sign_the_next_request() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY);
+        let credentials = RotatingCredentials::new(Auth::new("old-key", "partner"));
+        let config = Config::new("", "").with_credentials(credentials.clone());
+        let client = ApiClient::with_transport(config, transport.clone());
+
+        tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap();
+        credentials.rotate(Auth::new("new-key", "partner"));
+        tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        let signed_with = |request: &HttpRequest, key: &str| {
+            let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+            let timestamp = chrono::DateTime::parse_from_rfc3339(body["timestamp"].as_str().unwrap()).unwrap();
+            let signature = body["signature"].as_str().unwrap();
+            Auth::new(key, "partner")
+                .verify_signature(signature, &timestamp.with_timezone(&Utc))
+                .unwrap()
+        };
+        assert!(signed_with(&requests[0], "old-key"));
+        assert!(signed_with(&requests[1], "new-key"));
+    }
+
+    #[test]
+    fn services_are_fetched_unsigned_and_cached() {
+        let transport = FakeTransport::new(r#"{"id_types": {"NG": {"BVN": ["country", "id_type", "id_number"]}}}"#);
+        let config = Config::new("key", "partner").with_services_cache(Duration::from_secs(60));
+        let client = ApiClient::with_transport(config, transport.clone());
+
+        let first = tokio_test::block_on(client.services()).unwrap();
+        let second = tokio_test::block_on(client.clone().services()).unwrap();
+
+        assert_eq!(first.required_fields("NG", "BVN").unwrap(), ["country", "id_type", "id_number"]);
+        assert_eq!(second.countries().collect::<Vec<_>>(), ["NG"]);
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::Get);
+        assert_eq!(requests[0].url, "https://testapi.smileidentity.com/v1/services");
+        assert!(!requests[0].headers.iter().any(|(name, _)| name.starts_with("SmileID-")));
+    }
+
+    #[test]
+    fn services_are_retried_and_rate_limited_like_other_calls() {
+        let transport = FakeTransport::new(r#"{"id_types": {}}"#).queue(503, "unavailable");
+        let config = retrying_config(2).with_rate_limit(RateLimit::per_second(10).unwrap());
+        let client = ApiClient::with_transport(config, transport.clone());
+
+        assert!(tokio_test::block_on(client.services()).is_ok());
+        assert_eq!(transport.urls().len(), 2);
+        assert!(client.rate_limiter().unwrap().available() < 9.0);
+    }
+}
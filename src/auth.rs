@@ -1,45 +1,183 @@
 use base64::{engine::general_purpose, Engine};
 use hmac::{Hmac, Mac};
+#[cfg(feature = "legacy-sec-key")]
+use rsa::pkcs1::DecodeRsaPublicKey;
+#[cfg(feature = "legacy-sec-key")]
+use rsa::pkcs8::DecodePublicKey;
+#[cfg(feature = "legacy-sec-key")]
+use rsa::rand_core::OsRng;
+#[cfg(feature = "legacy-sec-key")]
+use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
+#[cfg(feature = "legacy-sec-key")]
+use sha2::Digest;
 use sha2::Sha256;
-use chrono::{Utc, DateTime};
+use chrono::{DateTime, SecondsFormat, Utc};
 use crate::error::{Error, Result};
+use crate::secret::ApiKey;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// How requests prove they were sent by the partner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// Base64 HMAC-SHA256 of the timestamp, partner ID and `sid_request`, keyed with the API key.
+    #[default]
+    Signature,
+    /// The legacy `sec_key`: a SHA-256 hash of `partner_id:timestamp`, RSA-encrypted
+    /// with the account's public key. Only for accounts that have not moved to signatures;
+    /// needs the `legacy-sec-key` feature.
+    #[cfg(feature = "legacy-sec-key")]
+    SecKey,
+}
+
 #[derive(Debug, Clone)]
 pub struct Auth {
-    api_key: String,
+    api_key: ApiKey,
     partner_id: String,
+    scheme: SignatureScheme,
 }
 
 impl Auth {
-    pub fn new(api_key: impl Into<String>, partner_id: impl Into<String>) -> Self {
+    pub fn new(api_key: impl Into<ApiKey>, partner_id: impl Into<String>) -> Self {
         Self {
             api_key: api_key.into(),
             partner_id: partner_id.into(),
+            scheme: SignatureScheme::default(),
         }
     }
-    
-    pub fn generate_signature(&self, timestamp: &DateTime<Utc>, payload: &str) -> Result<String> {
-        let timestamp_str = timestamp.to_rfc3339();
-        let message = format!("{}{}{}", self.partner_id, timestamp_str, payload);
-        
-        let mut mac = HmacSha256::new_from_slice(self.api_key.as_bytes())
-            .map_err(|e| Error::Auth(format!("Failed to create HMAC: {}", e)))?;
-        
-        mac.update(message.as_bytes());
-        let result = mac.finalize();
-        let signature = general_purpose::STANDARD.encode(result.into_bytes());
-        
+
+    pub fn with_scheme(mut self, scheme: SignatureScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    pub fn generate_signature(&self, timestamp: &DateTime<Utc>) -> Result<String> {
+        let mac = self.mac(&format_timestamp(timestamp))?;
+        let signature = general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+
         Ok(signature)
     }
-    
-    pub fn verify_signature(&self, signature: &str, timestamp: &DateTime<Utc>, payload: &str) -> Result<bool> {
-        let expected_signature = self.generate_signature(timestamp, payload)?;
-        Ok(signature == expected_signature)
+
+    /// Checks a signature in constant time. Use [`CallbackVerifier`](crate::CallbackVerifier)
+    /// for callbacks, which also enforces a time window.
+    pub fn verify_signature(&self, signature: &str, timestamp: &DateTime<Utc>) -> Result<bool> {
+        self.verify_signature_str(signature, &format_timestamp(timestamp))
+    }
+
+    /// Like [`verify_signature`](Self::verify_signature), for the timestamp exactly as it was sent.
+    pub(crate) fn verify_signature_str(&self, signature: &str, timestamp: &str) -> Result<bool> {
+        let Ok(signature) = general_purpose::STANDARD.decode(signature) else {
+            return Ok(false);
+        };
+
+        Ok(self.mac(timestamp)?.verify_slice(&signature).is_ok())
+    }
+
+    /// Generates a legacy `sec_key` for `timestamp` in seconds since the Unix epoch.
+    ///
+    /// The API key must be the base64-encoded RSA public key issued for the account.
+    #[cfg(feature = "legacy-sec-key")]
+    pub fn generate_sec_key(&self, timestamp: i64) -> Result<String> {
+        let partner_id: u64 = self
+            .partner_id
+            .trim()
+            .parse()
+            .map_err(|_| Error::Auth("sec_key signing needs a numeric partner ID".to_string()))?;
+        let hash = hex(&Sha256::digest(format!("{}:{}", partner_id, timestamp)));
+
+        let encrypted = self
+            .public_key()?
+            .encrypt(&mut OsRng, Pkcs1v15Encrypt, hash.as_bytes())
+            .map_err(|e| Error::Auth(format!("Failed to encrypt sec_key: {}", e)))?;
+
+        Ok(format!("{}|{}", general_purpose::STANDARD.encode(encrypted), hash))
     }
-    
+
     pub fn partner_id(&self) -> &str {
         &self.partner_id
     }
+
+    pub fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+
+    fn mac(&self, timestamp: &str) -> Result<HmacSha256> {
+        let mut mac = HmacSha256::new_from_slice(self.api_key.expose().as_bytes())
+            .map_err(|e| Error::Auth(format!("Failed to create HMAC: {}", e)))?;
+
+        mac.update(timestamp.as_bytes());
+        mac.update(self.partner_id.as_bytes());
+        mac.update(b"sid_request");
+        Ok(mac)
+    }
+
+    #[cfg(feature = "legacy-sec-key")]
+    fn public_key(&self) -> Result<RsaPublicKey> {
+        let decoded = general_purpose::STANDARD
+            .decode(self.api_key.expose().trim())
+            .map_err(|e| Error::Auth(format!("API key is not valid base64: {}", e)))?;
+
+        let key = match std::str::from_utf8(&decoded) {
+            Ok(pem) if pem.contains("-----BEGIN RSA PUBLIC KEY-----") => RsaPublicKey::from_pkcs1_pem(pem).ok(),
+            Ok(pem) if pem.contains("-----BEGIN") => RsaPublicKey::from_public_key_pem(pem).ok(),
+            _ => RsaPublicKey::from_public_key_der(&decoded)
+                .or_else(|_| RsaPublicKey::from_pkcs1_der(&decoded))
+                .ok(),
+        };
+
+        key.ok_or_else(|| Error::Auth("API key is not an RSA public key".to_string()))
+    }
+}
+
+/// Formats a timestamp the way Smile ID expects it, e.g. `2024-01-01T00:00:00.000Z`.
+pub(crate) fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(feature = "legacy-sec-key")]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn new_year() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn signature_matches_the_documented_scheme() {
+        let auth = Auth::new("test-api-key", "001");
+
+        let signature = auth.generate_signature(&new_year()).unwrap();
+
+        assert_eq!(signature, "QCvS8Dtz5XUe3wyeH3na5t1LKa3t0L0liNSoXwweCJ8=");
+        assert!(auth.verify_signature(&signature, &new_year()).unwrap());
+        assert!(!auth.verify_signature(&signature, &Utc::now()).unwrap());
+    }
+
+    #[cfg(feature = "legacy-sec-key")]
+    #[test]
+    fn sec_key_encrypts_the_hash_with_the_public_key() {
+        use rsa::pkcs8::EncodePublicKey;
+        use rsa::RsaPrivateKey;
+
+        let private_key = RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        let pem = RsaPublicKey::from(&private_key)
+            .to_public_key_pem(Default::default())
+            .unwrap();
+        let auth = Auth::new(general_purpose::STANDARD.encode(pem), "001").with_scheme(SignatureScheme::SecKey);
+
+        let sec_key = auth.generate_sec_key(1_704_067_200).unwrap();
+
+        let (encrypted, hash) = sec_key.split_once('|').unwrap();
+        assert_eq!(hash, "19e037af75f5694c24ba339a1628cff9c540ef4a98386c42f0b38191756f1953");
+        let decrypted = private_key
+            .decrypt(Pkcs1v15Encrypt, &general_purpose::STANDARD.decode(encrypted).unwrap())
+            .unwrap();
+        assert_eq!(decrypted, hash.as_bytes());
+    }
 }
@@ -0,0 +1,150 @@
+//! [axum](https://docs.rs/axum) integration for job callbacks.
+//!
+//! [`VerifiedCallback`] extracts an authenticated [`JobResult`] from a request,
+//! using the [`CallbackHandler`] in the router state, and [`router`] wires one
+//! up to your own async function.
+
+use ::axum::body::Bytes;
+use ::axum::extract::rejection::BytesRejection;
+use ::axum::extract::{FromRef, FromRequest, Request};
+use ::axum::http::StatusCode;
+use ::axum::response::{IntoResponse, Response};
+use ::axum::routing::post;
+use ::axum::Router;
+use std::future::Future;
+
+use super::{CallbackHandler, JobResult};
+use crate::error::Error;
+
+/// A job result whose signature has been verified.
+///
+/// Rejects the request with `401 Unauthorized` when the signature is missing,
+/// wrong or stale, and `400 Bad Request` when the body is not a job result.
+#[derive(Debug, Clone)]
+pub struct VerifiedCallback(pub JobResult);
+
+impl<S> FromRequest<S> for VerifiedCallback
+where
+    S: Send + Sync,
+    CallbackHandler: FromRef<S>,
+{
+    type Rejection = CallbackRejection;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let handler = CallbackHandler::from_ref(state);
+        let headers = request.headers().clone();
+        let body = Bytes::from_request(request, state)
+            .await
+            .map_err(CallbackRejection::Body)?;
+
+        let headers = headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        handler
+            .handle(headers, &body)
+            .map(VerifiedCallback)
+            .map_err(CallbackRejection::Invalid)
+    }
+}
+
+/// Why [`VerifiedCallback`] rejected a request.
+#[derive(Debug)]
+pub enum CallbackRejection {
+    /// The body could not be read.
+    Body(BytesRejection),
+    /// The body was not an authentic job result.
+    Invalid(Error),
+}
+
+impl IntoResponse for CallbackRejection {
+    fn into_response(self) -> Response {
+        match self {
+            CallbackRejection::Body(rejection) => rejection.into_response(),
+            CallbackRejection::Invalid(Error::SignatureVerification(_)) => {
+                (StatusCode::UNAUTHORIZED, "invalid callback signature").into_response()
+            }
+            CallbackRejection::Invalid(Error::Json(_)) => {
+                (StatusCode::BAD_REQUEST, "malformed callback body").into_response()
+            }
+            CallbackRejection::Invalid(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+/// A router that accepts callbacks posted to `path`, verifies them with
+/// `handler` and passes each job result to `on_result`.
+///
+/// Merge or nest it into your application's router. `on_result` can return
+/// anything that implements `IntoResponse`.
+pub fn router<F, Fut>(path: &str, handler: CallbackHandler, on_result: F) -> Router
+where
+    F: Fn(JobResult) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: IntoResponse,
+{
+    Router::new()
+        .route(
+            path,
+            post(move |VerifiedCallback(result): VerifiedCallback| on_result(result)),
+        )
+        .with_state(handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{format_timestamp, Auth};
+    use ::axum::body::Body;
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+
+    fn body(auth: &Auth) -> String {
+        let now = Utc::now();
+        serde_json::json!({
+            "ResultCode": "1012",
+            "SmileJobID": "0000000321",
+            "PartnerParams": { "job_id": "job-1", "user_id": "user-1" },
+            "signature": auth.generate_signature(&now).unwrap(),
+            "timestamp": format_timestamp(&now),
+        })
+        .to_string()
+    }
+
+    fn app(received: Arc<Mutex<Vec<String>>>) -> Router {
+        router("/callback", CallbackHandler::new(Auth::new("key", "001")), move |result: JobResult| {
+            let received = received.clone();
+            async move {
+                received.lock().unwrap().push(result.partner_params.job_id);
+                StatusCode::NO_CONTENT
+            }
+        })
+    }
+
+    async fn post_callback(app: Router, body: String) -> StatusCode {
+        let request = Request::post("/callback").body(Body::from(body)).unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn dispatches_verified_callbacks() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let status = post_callback(app(received.clone()), body(&Auth::new("key", "001"))).await;
+
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(*received.lock().unwrap(), ["job-1"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_signatures_and_bodies() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let forged = post_callback(app(received.clone()), body(&Auth::new("other-key", "001"))).await;
+        let malformed = post_callback(app(received.clone()), "{}".to_string()).await;
+
+        assert_eq!(forged, StatusCode::UNAUTHORIZED);
+        assert_eq!(malformed, StatusCode::BAD_REQUEST);
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
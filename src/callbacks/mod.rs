@@ -0,0 +1,227 @@
+//! Receiving job results that Smile ID posts to your callback URL.
+//!
+//! [`CallbackHandler::handle`] takes the request headers and raw body from
+//! whichever HTTP framework you use, checks the signature and returns the
+//! parsed [`JobResult`].
+
+mod verifier;
+#[cfg(feature = "axum")]
+pub mod axum;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::auth::Auth;
+use crate::error::{Error, Result};
+use crate::actions::Actions;
+use crate::result_code::ResultCode;
+
+pub use verifier::{CallbackVerifier, VerificationFailure};
+
+const SIGNATURE_HEADER: &str = "SmileID-Request-Signature";
+const TIMESTAMP_HEADER: &str = "SmileID-Timestamp";
+const PARTNER_ID_HEADER: &str = "SmileID-Partner-ID";
+
+/// A job result delivered to the callback URL.
+///
+/// Product-specific fields such as `FullName` or `IDNumber` are kept in `fields`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobResult {
+    #[serde(rename = "SmileJobID")]
+    pub smile_job_id: String,
+    pub partner_params: PartnerParams,
+    pub result_type: Option<String>,
+    pub result_text: Option<String>,
+    pub result_code: Option<ResultCode>,
+    #[serde(default)]
+    pub actions: Actions,
+    #[serde(rename = "signature")]
+    pub signature: Option<String>,
+    #[serde(rename = "timestamp")]
+    pub timestamp: Option<String>,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+/// The identifiers the job was submitted with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PartnerParams {
+    pub job_id: String,
+    pub user_id: String,
+    pub job_type: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Parses and authenticates job callbacks, independent of the HTTP server in use.
+#[derive(Debug, Clone)]
+pub struct CallbackHandler {
+    verifier: CallbackVerifier,
+}
+
+impl CallbackHandler {
+    pub fn new(auth: Auth) -> Self {
+        Self::with_verifier(CallbackVerifier::new(auth))
+    }
+
+    /// Uses `verifier`, e.g. one with a custom skew window or replay protection.
+    pub fn with_verifier(verifier: CallbackVerifier) -> Self {
+        Self { verifier }
+    }
+
+    /// Parses `body` and verifies its signature, returning the job result only
+    /// when the callback is authentic.
+    ///
+    /// The signature and timestamp are read from the body, falling back to the
+    /// `SmileID-Request-Signature` and `SmileID-Timestamp` headers. Header names
+    /// are matched case-insensitively.
+    pub fn handle<I, K, V>(&self, headers: I, body: &[u8]) -> Result<JobResult>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let (result, signed) = parse(headers, body)?;
+        self.verify(&signed)?;
+
+        Ok(result)
+    }
+
+    pub(crate) fn verify(&self, signed: &Signed) -> Result<()> {
+        match (&signed.signature, &signed.timestamp) {
+            (Some(signature), Some(timestamp)) => self.verifier.verify(signature, timestamp),
+            _ => Err(Error::SignatureVerification(VerificationFailure::MissingSignature)),
+        }
+    }
+}
+
+/// The signature, timestamp and partner ID a callback was sent with.
+pub(crate) struct Signed {
+    pub signature: Option<String>,
+    pub timestamp: Option<String>,
+    pub partner_id: Option<String>,
+}
+
+/// Parses a callback body, picking its signature out of the body or headers.
+pub(crate) fn parse<I, K, V>(headers: I, body: &[u8]) -> Result<(JobResult, Signed)>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let result: JobResult = serde_json::from_slice(body).map_err(Error::Json)?;
+
+    let mut signed = Signed {
+        signature: result.signature.clone(),
+        timestamp: result.timestamp.clone(),
+        partner_id: result.fields.get("partner_id").and_then(Value::as_str).map(str::to_string),
+    };
+    for (name, value) in headers {
+        let (name, value) = (name.as_ref(), value.as_ref());
+        let field = if name.eq_ignore_ascii_case(SIGNATURE_HEADER) {
+            &mut signed.signature
+        } else if name.eq_ignore_ascii_case(TIMESTAMP_HEADER) {
+            &mut signed.timestamp
+        } else if name.eq_ignore_ascii_case(PARTNER_ID_HEADER) {
+            &mut signed.partner_id
+        } else {
+            continue;
+        };
+        field.get_or_insert_with(|| value.to_string());
+    }
+
+    Ok((result, signed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::ActionResult;
+    use crate::auth::format_timestamp;
+    use chrono::Utc;
+
+    fn callback(signature: &str, timestamp: &str) -> String {
+        serde_json::json!({
+            "Actions": { "Verify_ID_Number": "Verified", "Return_Personal_Info": "Returned" },
+            "ResultCode": "1012",
+            "ResultText": "ID Number Validated",
+            "ResultType": "ID Verification",
+            "SmileJobID": "0000000321",
+            "PartnerParams": { "job_id": "job-1", "user_id": "user-1", "job_type": 5 },
+            "FullName": "Jane Doe",
+            "signature": signature,
+            "timestamp": timestamp,
+        })
+        .to_string()
+    }
+
+    fn signed_now(auth: &Auth) -> (String, String) {
+        let now = Utc::now();
+        (auth.generate_signature(&now).unwrap(), format_timestamp(&now))
+    }
+
+    #[test]
+    fn parses_authentic_callbacks() {
+        let auth = Auth::new("key", "001");
+        let (signature, timestamp) = signed_now(&auth);
+        let body = callback(&signature, &timestamp);
+
+        let result = CallbackHandler::new(auth)
+            .handle(Vec::<(&str, &str)>::new(), body.as_bytes())
+            .unwrap();
+
+        assert_eq!(result.smile_job_id, "0000000321");
+        assert_eq!(result.partner_params.job_id, "job-1");
+        assert_eq!(result.result_code, Some(ResultCode::IdValidated));
+        assert_eq!(result.actions.verify_id_number, Some(ActionResult::Verified));
+        assert_eq!(result.fields["FullName"], "Jane Doe");
+    }
+
+    #[test]
+    fn falls_back_to_signature_headers() {
+        let auth = Auth::new("key", "001");
+        let (signature, timestamp) = signed_now(&auth);
+        let mut body: Value = serde_json::from_str(&callback("", "")).unwrap();
+        body.as_object_mut().unwrap().retain(|key, _| key != "signature" && key != "timestamp");
+        let headers = [("smileid-request-signature", signature), ("SMILEID-TIMESTAMP", timestamp)];
+
+        let result = CallbackHandler::new(auth).handle(headers, body.to_string().as_bytes());
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn replays_with_a_different_job_id_are_rejected() {
+        let auth = Auth::new("key", "001");
+        let handler = CallbackHandler::with_verifier(CallbackVerifier::new(auth.clone()).with_replay_protection());
+        let (signature, timestamp) = signed_now(&auth);
+        let body = callback(&signature, &timestamp);
+        let mut replayed: Value = serde_json::from_str(&body).unwrap();
+        replayed["SmileJobID"] = "0000000999".into();
+
+        assert!(handler.handle(Vec::<(&str, &str)>::new(), body.as_bytes()).is_ok());
+        assert!(matches!(
+            handler.handle(Vec::<(&str, &str)>::new(), replayed.to_string().as_bytes()),
+            Err(Error::SignatureVerification(VerificationFailure::Replayed))
+        ));
+    }
+
+    #[test]
+    fn rejects_forged_and_unsigned_callbacks() {
+        let handler = CallbackHandler::new(Auth::new("key", "001"));
+        let (signature, timestamp) = signed_now(&Auth::new("other-key", "001"));
+        let forged = callback(&signature, &timestamp);
+        let mut unsigned: Value = serde_json::from_str(&forged).unwrap();
+        unsigned.as_object_mut().unwrap().remove("signature");
+
+        assert!(matches!(
+            handler.handle(Vec::<(&str, &str)>::new(), forged.as_bytes()),
+            Err(Error::SignatureVerification(VerificationFailure::InvalidSignature))
+        ));
+        assert!(matches!(
+            handler.handle(Vec::<(&str, &str)>::new(), unsigned.to_string().as_bytes()),
+            Err(Error::SignatureVerification(VerificationFailure::MissingSignature))
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::auth::Auth;
+use crate::credentials::CredentialProvider;
+use crate::error::{Error, Result};
+
+/// Signature and timestamp pairs accepted within the window, and when they were sent.
+type SeenSignatures = Arc<Mutex<HashMap<(String, String), DateTime<Utc>>>>;
+
+/// Why a callback signature was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationFailure {
+    /// The callback carried no signature or no timestamp.
+    MissingSignature,
+    /// The timestamp could not be parsed.
+    MalformedTimestamp(String),
+    /// The signature does not match the timestamp and partner ID.
+    InvalidSignature,
+    /// The timestamp is further from the current time than the allowed skew.
+    OutsideWindow { skew: Duration },
+    /// The signature and timestamp were already accepted within the window.
+    Replayed,
+}
+
+impl fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationFailure::MissingSignature => write!(f, "signature or timestamp is missing"),
+            VerificationFailure::MalformedTimestamp(timestamp) => write!(f, "malformed timestamp {:?}", timestamp),
+            VerificationFailure::InvalidSignature => write!(f, "signature does not match"),
+            VerificationFailure::OutsideWindow { skew } => write!(f, "timestamp is {:?} away from now", skew),
+            VerificationFailure::Replayed => write!(f, "signature was already used"),
+        }
+    }
+}
+
+/// Verifies the `signature` and `timestamp` Smile ID sends with job callbacks.
+///
+/// Signatures are compared in constant time and timestamps more than
+/// `max_skew` away from the current time are rejected. With replay protection
+/// enabled, each signature and timestamp pair is accepted once within the
+/// window; the record of seen signatures is shared by clones of the verifier.
+///
+/// A signature only covers the timestamp and partner ID. Timestamps carry
+/// milliseconds, so only callbacks sent in the same millisecond share one.
+/// Nothing else in the body, such as the `SmileJobID`, is authenticated, so
+/// none of it is part of the replay key.
+///
+/// A signature made with any of the provider's verification keys is accepted,
+/// so callbacks keep verifying while keys are rotated.
+#[derive(Debug, Clone)]
+pub struct CallbackVerifier {
+    credentials: Arc<dyn CredentialProvider>,
+    max_skew: Duration,
+    seen: Option<SeenSignatures>,
+}
+
+impl CallbackVerifier {
+    pub fn new(auth: Auth) -> Self {
+        Self::from_credentials(auth)
+    }
+
+    pub fn from_credentials(credentials: impl CredentialProvider + 'static) -> Self {
+        Self {
+            credentials: Arc::new(credentials),
+            max_skew: Duration::from_secs(5 * 60),
+            seen: None,
+        }
+    }
+
+    pub fn with_max_skew(mut self, max_skew: Duration) -> Self {
+        self.max_skew = max_skew;
+        self
+    }
+
+    pub fn with_replay_protection(mut self) -> Self {
+        self.seen = Some(Arc::new(Mutex::new(HashMap::new())));
+        self
+    }
+
+    pub fn verify(&self, signature: &str, timestamp: &str) -> Result<()> {
+        self.verify_at(signature, timestamp, Utc::now())
+    }
+
+    fn verify_at(&self, signature: &str, timestamp: &str, now: DateTime<Utc>) -> Result<()> {
+        let sent_at = DateTime::parse_from_rfc3339(timestamp)
+            .map_err(|_| rejected(VerificationFailure::MalformedTimestamp(timestamp.to_string())))?
+            .with_timezone(&Utc);
+
+        let mut valid = false;
+        for auth in self.credentials.verification_keys()? {
+            valid |= auth.verify_signature_str(signature, timestamp)?;
+        }
+        if !valid {
+            return Err(rejected(VerificationFailure::InvalidSignature));
+        }
+
+        let skew = (now - sent_at).abs().to_std().unwrap_or(Duration::MAX);
+        if skew > self.max_skew {
+            return Err(rejected(VerificationFailure::OutsideWindow { skew }));
+        }
+
+        if let Some(seen) = &self.seen {
+            let mut seen = seen.lock().unwrap_or_else(|e| e.into_inner());
+            let max_skew = chrono::Duration::from_std(self.max_skew).unwrap_or(chrono::Duration::MAX);
+            seen.retain(|_, sent_at| (now - *sent_at).abs() <= max_skew);
+
+            if seen.insert((signature.to_string(), timestamp.to_string()), sent_at).is_some() {
+                return Err(rejected(VerificationFailure::Replayed));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn rejected(reason: VerificationFailure) -> Error {
+    Error::SignatureVerification(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::format_timestamp;
+    use crate::credentials::RotatingCredentials;
+
+    fn signed(auth: &Auth, sent_at: DateTime<Utc>) -> (String, String) {
+        (auth.generate_signature(&sent_at).unwrap(), format_timestamp(&sent_at))
+    }
+
+    #[test]
+    fn accepts_fresh_signatures_and_rejects_tampered_ones() {
+        let auth = Auth::new("key", "001");
+        let verifier = CallbackVerifier::new(auth.clone());
+        let now = Utc::now();
+        let (signature, timestamp) = signed(&auth, now);
+
+        assert!(verifier.verify_at(&signature, &timestamp, now).is_ok());
+        assert!(matches!(
+            verifier.verify_at("bm90IGl0", &timestamp, now),
+            Err(Error::SignatureVerification(VerificationFailure::InvalidSignature))
+        ));
+        assert!(matches!(
+            verifier.verify_at(&signature, "yesterday", now),
+            Err(Error::SignatureVerification(VerificationFailure::MalformedTimestamp(_)))
+        ));
+    }
+
+    #[test]
+    fn rejects_timestamps_outside_the_window() {
+        let auth = Auth::new("key", "001");
+        let verifier = CallbackVerifier::new(auth.clone()).with_max_skew(Duration::from_secs(60));
+        let now = Utc::now();
+        let (signature, timestamp) = signed(&auth, now - chrono::Duration::minutes(2));
+
+        assert!(matches!(
+            verifier.verify_at(&signature, &timestamp, now),
+            Err(Error::SignatureVerification(VerificationFailure::OutsideWindow { .. }))
+        ));
+    }
+
+    #[test]
+    fn replayed_signatures_are_rejected_across_clones() {
+        let auth = Auth::new("key", "001");
+        let verifier = CallbackVerifier::new(auth.clone()).with_replay_protection();
+        let now = Utc::now();
+        let (signature, timestamp) = signed(&auth, now);
+
+        assert!(verifier.verify_at(&signature, &timestamp, now).is_ok());
+        assert!(matches!(
+            verifier.clone().verify_at(&signature, &timestamp, now),
+            Err(Error::SignatureVerification(VerificationFailure::Replayed))
+        ));
+    }
+
+    #[test]
+    fn callbacks_sent_a_millisecond_apart_are_not_replays() {
+        let auth = Auth::new("key", "001");
+        let verifier = CallbackVerifier::new(auth.clone()).with_replay_protection();
+        let now = Utc::now();
+        let (signature, timestamp) = signed(&auth, now);
+        let (next_signature, next_timestamp) = signed(&auth, now + chrono::Duration::milliseconds(1));
+
+        assert!(verifier.verify_at(&signature, &timestamp, now).is_ok());
+        assert!(verifier.verify_at(&next_signature, &next_timestamp, now).is_ok());
+    }
+
+    #[test]
+    fn accepts_every_active_key_during_rotation() {
+        let old = Auth::new("old-key", "001");
+        let new = Auth::new("new-key", "001");
+        let credentials = RotatingCredentials::new(old.clone());
+        let verifier = CallbackVerifier::from_credentials(credentials.clone());
+        let now = Utc::now();
+        let (signature, timestamp) = signed(&old, now);
+
+        credentials.rotate(new.clone());
+        assert!(verifier.verify_at(&signature, &timestamp, now).is_ok());
+
+        credentials.retire_previous();
+        let (fresh_signature, fresh_timestamp) = signed(&new, now);
+        assert!(verifier.verify_at(&fresh_signature, &fresh_timestamp, now).is_ok());
+        assert!(matches!(
+            verifier.verify_at(&signature, &timestamp, now),
+            Err(Error::SignatureVerification(VerificationFailure::InvalidSignature))
+        ));
+    }
+}
@@ -0,0 +1,249 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::transport::HttpResponse;
+
+/// When to open the circuit and how long to keep it open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive transport failures or 5xx responses that open the circuit.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a single probe request is let through.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally.
+    Closed,
+    /// Requests fail fast with [`Error::CircuitOpen`].
+    Open,
+    /// The cooldown has elapsed and a probe request decides whether to close the circuit.
+    HalfOpen,
+}
+
+/// A circuit breaker shared by every clone of the client it belongs to.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe: Option<Probe>,
+    probes_started: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Probe {
+    id: u64,
+    started_at: Instant,
+}
+
+/// A request [`CircuitBreaker::check`] let through, to be handed back to
+/// [`CircuitBreaker::record`] with its outcome.
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitPermit {
+    probe: Option<u64>,
+}
+
+impl CircuitPermit {
+    /// Whether this is the single probe of a half-open circuit.
+    pub fn is_probe(&self) -> bool {
+        self.probe.is_some()
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match inner.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.config.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).consecutive_failures
+    }
+
+    /// Returns [`Error::CircuitOpen`] unless a request may be sent now.
+    ///
+    /// While half-open only one probe is let through at a time; a probe that
+    /// never reports back is abandoned after another cooldown.
+    pub fn check(&self) -> Result<CircuitPermit> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(opened_at) = inner.opened_at else {
+            return Ok(CircuitPermit { probe: None });
+        };
+
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.config.cooldown {
+            return Err(Error::CircuitOpen {
+                retry_after: self.config.cooldown.saturating_sub(elapsed),
+            });
+        }
+
+        match inner.probe.map(|probe| probe.started_at.elapsed()) {
+            Some(probing) if probing < self.config.cooldown => Err(Error::CircuitOpen {
+                retry_after: self.config.cooldown.saturating_sub(probing),
+            }),
+            _ => {
+                inner.probes_started += 1;
+                let id = inner.probes_started;
+                inner.probe = Some(Probe {
+                    id,
+                    started_at: Instant::now(),
+                });
+                Ok(CircuitPermit { probe: Some(id) })
+            }
+        }
+    }
+
+    /// Records the outcome of the request `permit` let through. Transport
+    /// errors and 5xx responses count as failures.
+    ///
+    /// Only the current probe decides whether an open circuit closes or opens
+    /// again; requests that were already in flight when it opened, and probes
+    /// that were abandoned, only count towards the failures.
+    pub fn record(&self, permit: CircuitPermit, outcome: &Result<HttpResponse>) {
+        let failed = match outcome {
+            Ok(response) => response.status >= 500,
+            Err(_) => true,
+        };
+
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let probe = permit.probe.is_some() && permit.probe == inner.probe.map(|probe| probe.id);
+        if probe {
+            inner.probe = None;
+        }
+
+        if !failed {
+            if probe || inner.opened_at.is_none() {
+                inner.consecutive_failures = 0;
+                inner.opened_at = None;
+            }
+            return;
+        }
+
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        if probe || (inner.opened_at.is_none() && inner.consecutive_failures >= self.config.failure_threshold) {
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16) -> Result<HttpResponse> {
+        Ok(HttpResponse {
+            status,
+            body: String::new(),
+        })
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures_and_fails_fast() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::new(2, Duration::from_secs(60)));
+
+        breaker.record(breaker.check().unwrap(), &response(503));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record(breaker.check().unwrap(), &Err(Error::Transport("connection reset".to_string())));
+
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(breaker.check(), Err(Error::CircuitOpen { .. })));
+    }
+
+    #[test]
+    fn half_open_probe_closes_the_circuit_on_success() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::new(1, Duration::ZERO));
+        breaker.record(breaker.check().unwrap(), &response(500));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let probe = breaker.check().unwrap();
+        assert!(probe.is_probe());
+        breaker.record(probe, &response(400));
+
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn only_one_probe_is_let_through_while_half_open() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::new(1, Duration::from_millis(20)));
+        breaker.record(breaker.check().unwrap(), &response(500));
+        std::thread::sleep(Duration::from_millis(25));
+
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_err());
+    }
+
+    #[test]
+    fn only_the_probe_closes_an_open_circuit() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::new(1, Duration::from_millis(50)));
+        let in_flight = breaker.check().unwrap();
+        breaker.record(breaker.check().unwrap(), &response(500));
+        std::thread::sleep(Duration::from_millis(60));
+
+        let probe = breaker.check().unwrap();
+        breaker.record(in_flight, &response(200));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.check().is_err(), "the probe is still outstanding");
+
+        breaker.record(probe, &response(200));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn an_abandoned_probe_does_not_settle_its_successor() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::new(1, Duration::from_millis(20)));
+        breaker.record(breaker.check().unwrap(), &response(500));
+        std::thread::sleep(Duration::from_millis(25));
+        let abandoned = breaker.check().unwrap();
+        std::thread::sleep(Duration::from_millis(25));
+        let probe = breaker.check().unwrap();
+
+        breaker.record(abandoned, &response(200));
+        assert_ne!(breaker.state(), CircuitState::Closed);
+
+        breaker.record(probe, &response(500));
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+}
@@ -1,35 +1,376 @@
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+use crate::auth::{Auth, SignatureScheme};
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::credentials::CredentialProvider;
+use crate::error::{Error, Result};
+use crate::rate_limit::RateLimit;
+use crate::secret::ApiKey;
+use crate::transport::HttpOptions;
+use crate::retry::RetryPolicy;
+
+/// The Smile ID deployment a client talks to.
+///
+/// Each environment carries its own host and API version path, so sandbox
+/// credentials are never sent to production by accident.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Environment {
+    #[default]
+    Sandbox,
+    Production,
+    /// A full base URL including the version path, e.g. `http://localhost:8080/v1`.
+    Custom(String),
+}
+
+impl Environment {
+    /// The URL every endpoint path is appended to.
+    pub fn base_url(&self) -> &str {
+        match self {
+            Environment::Sandbox => "https://testapi.smileidentity.com/v1",
+            Environment::Production => "https://api.smileidentity.com/v1",
+            Environment::Custom(url) => url.trim_end_matches('/'),
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = Error;
+
+    /// Parses `sandbox`, `production` or an `http(s)://` base URL.
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            v if v.eq_ignore_ascii_case("sandbox") => Ok(Environment::Sandbox),
+            v if v.eq_ignore_ascii_case("production") => Ok(Environment::Production),
+            v if v.starts_with("http://") || v.starts_with("https://") => Ok(Environment::Custom(v.to_string())),
+            v => Err(Error::Config(format!(
+                "Unknown environment {:?}, expected sandbox, production or a base URL",
+                v
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Environment::Sandbox => write!(f, "sandbox"),
+            Environment::Production => write!(f, "production"),
+            Environment::Custom(url) => write!(f, "custom ({})", url),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub api_key: String,
+    pub api_key: ApiKey,
     pub partner_id: String,
-    pub base_url: String,
-    pub version: String,
+    pub environment: Environment,
+    pub signature_scheme: SignatureScheme,
+    pub credentials: Option<Arc<dyn CredentialProvider>>,
     pub timeout: u64,
+    pub http: HttpOptions,
+    pub retry: RetryPolicy,
+    pub rate_limit: Option<RateLimit>,
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// How long `ApiClient::services` reuses a response. `None` fetches every time.
+    pub services_cache_ttl: Option<Duration>,
 }
 
 impl Config {
-    pub fn new(api_key: impl Into<String>, partner_id: impl Into<String>) -> Self {
+    pub fn new(api_key: impl Into<ApiKey>, partner_id: impl Into<String>) -> Self {
         Self {
             api_key: api_key.into(),
             partner_id: partner_id.into(),
-            base_url: "https://api.usesmileid.com".to_string(),
-            version: "1.0".to_string(),
+            environment: Environment::Sandbox,
+            signature_scheme: SignatureScheme::default(),
+            credentials: None,
             timeout: 30,
+            http: HttpOptions::default(),
+            retry: RetryPolicy::none(),
+            rate_limit: None,
+            circuit_breaker: None,
+            services_cache_ttl: None,
         }
     }
     
+    /// Reads `SMILE_ID_API_KEY`, `SMILE_ID_PARTNER_ID` and the optional
+    /// `SMILE_ID_ENV` and `SMILE_ID_TIMEOUT` (in seconds) variables.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let timeout = var("SMILE_ID_TIMEOUT")
+            .map(|timeout| {
+                timeout.trim().parse().map_err(|_| {
+                    Error::Config(format!("SMILE_ID_TIMEOUT must be a number of seconds, got {:?}", timeout))
+                })
+            })
+            .transpose()?;
+
+        ConfigFile {
+            api_key: var("SMILE_ID_API_KEY").map(ApiKey::new),
+            partner_id: var("SMILE_ID_PARTNER_ID"),
+            environment: var("SMILE_ID_ENV"),
+            timeout,
+        }
+        .into_config("SMILE_ID_API_KEY", "SMILE_ID_PARTNER_ID")
+    }
+
+    /// Loads a `.toml` or `.json` file with `api_key`, `partner_id` and the
+    /// optional `environment` and `timeout` keys.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map(Zeroizing::new)
+            .map_err(|e| Error::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+
+        let file: ConfigFile = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)
+                .map_err(|e| Error::Config(format!("Invalid TOML in {}: {}", path.display(), e)))?,
+            Some("json") => serde_json::from_str(&contents)
+                .map_err(|e| Error::Config(format!("Invalid JSON in {}: {}", path.display(), e)))?,
+            _ => {
+                return Err(Error::Config(format!(
+                    "Unsupported config file {}, expected .toml or .json",
+                    path.display()
+                )))
+            }
+        };
+
+        file.into_config("api_key", "partner_id")
+    }
+
+    /// The provider set with [`with_credentials`](Self::with_credentials), or
+    /// one that always returns `api_key` and `partner_id`.
+    pub(crate) fn credential_provider(&self) -> Arc<dyn CredentialProvider> {
+        self.credentials.clone().unwrap_or_else(|| {
+            Arc::new(Auth::new(self.api_key.clone(), &self.partner_id).with_scheme(self.signature_scheme))
+        })
+    }
+
+    /// Checks the values that would otherwise only fail once a request is signed or sent.
+    pub fn validate(&self) -> Result<()> {
+        if self.credentials.is_none() && self.api_key.is_empty() {
+            return Err(Error::Config("API key must not be empty".to_string()));
+        }
+        if self.credentials.is_none() && self.partner_id.trim().is_empty() {
+            return Err(Error::Config("Partner ID must not be empty".to_string()));
+        }
+        if self.timeout == 0 {
+            return Err(Error::Config("Timeout must be at least one second".to_string()));
+        }
+        if let Environment::Custom(url) = &self.environment {
+            url::Url::parse(url).map_err(|e| Error::Config(format!("Invalid base URL {:?}: {}", url, e)))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Shorthand for `with_environment(Environment::Custom(base_url))`.
     pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
-        self.base_url = base_url.into();
+        self.environment = Environment::Custom(base_url.into());
         self
     }
-    
+
+    /// The URL every endpoint path is appended to, including the version path.
+    #[deprecated(note = "use `environment.base_url()`")]
+    pub fn base_url(&self) -> &str {
+        self.environment.base_url()
+    }
+
+    /// Replaces the `/v...` version path of the current environment's base URL.
+    #[deprecated(note = "the version path is part of the environment; use `with_environment`")]
     pub fn with_version(mut self, version: impl Into<String>) -> Self {
-        self.version = version.into();
+        let base_url = self.environment.base_url();
+        let host = base_url.rsplit_once("/v").map_or(base_url, |(host, _)| host);
+        self.environment = Environment::Custom(format!("{}/v{}", host, version.into()));
         self
     }
     
+    /// Opts into the legacy `sec_key` scheme for older accounts, see [`SignatureScheme`].
+    pub fn with_signature_scheme(mut self, signature_scheme: SignatureScheme) -> Self {
+        self.signature_scheme = signature_scheme;
+        self
+    }
+
+    /// Signs requests with whatever `credentials` currently supplies, e.g.
+    /// [`RotatingCredentials`](crate::RotatingCredentials), instead of `api_key`,
+    /// `partner_id` and `signature_scheme`.
+    pub fn with_credentials(mut self, credentials: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(Arc::new(credentials));
+        self
+    }
+
     pub fn with_timeout(mut self, timeout: u64) -> Self {
         self.timeout = timeout;
         self
     }
+
+    pub fn with_http_options(mut self, http: HttpOptions) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Caches the `/services` response in memory for `ttl`, shared by clones of the client.
+    pub fn with_services_cache(mut self, ttl: Duration) -> Self {
+        self.services_cache_ttl = Some(ttl);
+        self
+    }
+}
+
+/// The settings accepted by [`Config::from_env`] and [`Config::from_file`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    api_key: Option<ApiKey>,
+    partner_id: Option<String>,
+    environment: Option<String>,
+    timeout: Option<u64>,
+}
+
+impl ConfigFile {
+    fn into_config(self, api_key_name: &str, partner_id_name: &str) -> Result<Config> {
+        let api_key = self
+            .api_key
+            .ok_or_else(|| Error::Config(format!("{} is missing", api_key_name)))?;
+        let partner_id = self
+            .partner_id
+            .ok_or_else(|| Error::Config(format!("{} is missing", partner_id_name)))?;
+
+        let mut config = Config::new(api_key, partner_id);
+        if let Some(environment) = self.environment {
+            config.environment = environment.parse()?;
+        }
+        if let Some(timeout) = self.timeout {
+            config.timeout = timeout;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_configs_default_to_the_sandbox() {
+        let config = Config::new("key", "partner");
+
+        assert_eq!(config.environment, Environment::Sandbox);
+        assert_eq!(config.environment.base_url(), "https://testapi.smileidentity.com/v1");
+    }
+
+    #[test]
+    fn debug_output_redacts_the_api_key() {
+        let config = Config::new("super-secret", "partner");
+
+        assert!(!format!("{:?}", config).contains("super-secret"));
+    }
+
+    #[test]
+    fn custom_base_urls_are_used_as_given() {
+        let config = Config::new("key", "partner").with_base_url("http://localhost:8080/v1/");
+
+        assert_eq!(config.environment.base_url(), "http://localhost:8080/v1");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn with_version_keeps_the_host() {
+        let config = Config::new("key", "partner").with_version("2");
+
+        assert_eq!(config.base_url(), "https://testapi.smileidentity.com/v2");
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: std::collections::HashMap<String, String> = pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn env_vars_configure_credentials_environment_and_timeout() {
+        let config = Config::from_vars(vars(&[
+            ("SMILE_ID_API_KEY", "key"),
+            ("SMILE_ID_PARTNER_ID", "partner"),
+            ("SMILE_ID_ENV", "Production"),
+            ("SMILE_ID_TIMEOUT", "10"),
+        ]))
+        .unwrap();
+
+        assert_eq!(config.api_key.expose(), "key");
+        assert_eq!(config.partner_id, "partner");
+        assert_eq!(config.environment, Environment::Production);
+        assert_eq!(config.timeout, 10);
+    }
+
+    #[test]
+    fn missing_or_malformed_env_vars_are_config_errors() {
+        let missing = Config::from_vars(vars(&[("SMILE_ID_API_KEY", "key")])).unwrap_err();
+        let bad_timeout = Config::from_vars(vars(&[
+            ("SMILE_ID_API_KEY", "key"),
+            ("SMILE_ID_PARTNER_ID", "partner"),
+            ("SMILE_ID_TIMEOUT", "soon"),
+        ]))
+        .unwrap_err();
+        let bad_env = Config::from_vars(vars(&[
+            ("SMILE_ID_API_KEY", "key"),
+            ("SMILE_ID_PARTNER_ID", "partner"),
+            ("SMILE_ID_ENV", "staging"),
+        ]))
+        .unwrap_err();
+
+        assert!(matches!(missing, Error::Config(ref message) if message.contains("SMILE_ID_PARTNER_ID")));
+        assert!(matches!(bad_timeout, Error::Config(_)));
+        assert!(matches!(bad_env, Error::Config(_)));
+    }
+
+    #[test]
+    fn toml_and_json_files_are_loaded() {
+        let dir = std::env::temp_dir().join(format!("smile_id_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let toml_path = dir.join("smile_id.toml");
+        let json_path = dir.join("smile_id.json");
+        std::fs::write(&toml_path, "api_key = \"key\"\npartner_id = \"partner\"\ntimeout = 5\n").unwrap();
+        std::fs::write(&json_path, r#"{"api_key": "key", "partner_id": "", "environment": "sandbox"}"#).unwrap();
+
+        let config = Config::from_file(&toml_path).unwrap();
+        let err = Config::from_file(&json_path).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(config.timeout, 5);
+        assert_eq!(config.environment, Environment::Sandbox);
+        assert!(matches!(err, Error::Config(_)));
+    }
 }
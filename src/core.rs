@@ -0,0 +1,373 @@
+//! The IO-free half of the clients.
+//!
+//! [`Core`] builds and signs requests, runs interceptors and bookkeeping, and
+//! turns responses into results. The async and blocking `ApiClient`s only
+//! wait, sleep and hand requests to their transport, so every option, product
+//! and error path behaves the same in both modes.
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::auth::{format_timestamp, Auth, SignatureScheme};
+use crate::circuit_breaker::{CircuitBreaker, CircuitPermit};
+use crate::config::{Config, Environment};
+use crate::credentials::CredentialProvider;
+use crate::error::{Error, Result};
+use crate::interceptor::Interceptor;
+use crate::models::ApiResponse;
+use crate::options::RequestOptions;
+use crate::rate_limit::RateLimiter;
+use crate::retry;
+use crate::services::ServicesCache;
+use crate::telemetry::RequestSpan;
+use crate::transport::{HttpRequest, HttpResponse, Method};
+
+/// A call to one endpoint: the path under the base URL and the payload to send.
+pub(crate) struct Call<P> {
+    pub path: &'static str,
+    /// Whether sending the payload twice is harmless, as for lookups.
+    pub idempotent: bool,
+    pub payload: P,
+}
+
+/// State shared by an `ApiClient` and its clones, independent of how requests are sent.
+#[derive(Debug, Clone)]
+pub(crate) struct Core {
+    credentials: Arc<dyn CredentialProvider>,
+    config: Config,
+    rate_limiter: Option<RateLimiter>,
+    circuit_breaker: Option<CircuitBreaker>,
+    services_cache: Option<ServicesCache>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+}
+
+/// An attempt that has been signed and is about to be sent.
+pub(crate) struct Attempt {
+    number: u32,
+    sent: Option<HttpRequest>,
+    started: Instant,
+}
+
+impl Core {
+    pub fn new(config: Config) -> Self {
+        Self {
+            credentials: config.credential_provider(),
+            rate_limiter: config.rate_limit.clone().map(RateLimiter::new),
+            circuit_breaker: config.circuit_breaker.clone().map(CircuitBreaker::new),
+            services_cache: config.services_cache_ttl.map(ServicesCache::new),
+            interceptors: Vec::new(),
+            config,
+        }
+    }
+
+    pub fn add_interceptor(&mut self, interceptor: Arc<dyn Interceptor>) {
+        self.interceptors.push(interceptor);
+    }
+
+    pub fn base_url(&self) -> String {
+        self.config.environment.base_url().to_string()
+    }
+
+    pub fn environment(&self) -> &Environment {
+        &self.config.environment
+    }
+
+    pub fn url<P>(&self, call: &Call<P>) -> String {
+        format!("{}/{}", self.base_url(), call.path)
+    }
+
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_ref()
+    }
+
+    pub fn span(&self, url: &str) -> RequestSpan {
+        let partner_id = self.credentials.current().map(|auth| auth.partner_id().to_string());
+        RequestSpan::new(partner_id.as_deref().unwrap_or_default(), &self.config.environment, url)
+    }
+
+    /// Fails fast when the call was cancelled or the circuit is open, and
+    /// otherwise returns the circuit breaker's permit for the attempt.
+    pub fn check(&self, options: &RequestOptions) -> Result<Option<CircuitPermit>> {
+        if options.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
+        self.circuit_breaker.as_ref().map(CircuitBreaker::check).transpose()
+    }
+
+    /// Builds and signs attempt number `number`, once any rate limit has been waited out.
+    pub fn prepare<T>(
+        &self,
+        url: &str,
+        payload: &T,
+        options: &RequestOptions,
+        number: u32,
+    ) -> Result<(HttpRequest, Attempt)>
+    where
+        T: Serialize + ?Sized,
+    {
+        let auth = self.credentials.current()?;
+        let request = signed_request(&auth, &self.interceptors, url, payload, options)?;
+        let attempt = self.attempt(&request, number);
+
+        Ok((request, attempt))
+    }
+
+    pub fn services_url(&self) -> String {
+        format!("{}/services", self.base_url())
+    }
+
+    /// The `/services` cache shared by this client and its clones, if caching is enabled.
+    pub fn services_cache(&self) -> Option<&ServicesCache> {
+        self.services_cache.as_ref()
+    }
+
+    /// Builds attempt number `number` of the unsigned `GET /services` request.
+    pub fn prepare_services(&self, options: &RequestOptions, number: u32) -> Result<(HttpRequest, Attempt)> {
+        let mut request = HttpRequest::new(Method::Get, self.services_url());
+        request.headers = options.headers.clone();
+        request.timeout = options.timeout;
+        for interceptor in &self.interceptors {
+            interceptor.before_send(&mut request)?;
+        }
+        let attempt = self.attempt(&request, number);
+
+        Ok((request, attempt))
+    }
+
+    fn attempt(&self, request: &HttpRequest, number: u32) -> Attempt {
+        Attempt {
+            number,
+            sent: (!self.interceptors.is_empty()).then(|| request.clone()),
+            started: Instant::now(),
+        }
+    }
+
+    /// Records what the transport returned for `attempt`, which `permit` let
+    /// through, and parses the response.
+    pub fn complete<R>(
+        &self,
+        attempt: Attempt,
+        permit: Option<CircuitPermit>,
+        response: Result<HttpResponse>,
+        span: &RequestSpan,
+    ) -> Result<R>
+    where
+        R: DeserializeOwned,
+    {
+        span.record_attempt(attempt.number, attempt.started, &response);
+        if let Some(request) = &attempt.sent {
+            for interceptor in self.interceptors.iter().rev() {
+                interceptor.after_receive(request, &response);
+            }
+        }
+        if let (Some(circuit_breaker), Some(permit)) = (&self.circuit_breaker, permit) {
+            circuit_breaker.record(permit, &response);
+        }
+
+        parse_response(response?, &self.config.environment)
+    }
+
+    /// How long to wait before retrying after attempt number `attempt` failed
+    /// with `error`, or the error to return when no retry is due.
+    pub fn retry_delay(&self, error: Error, attempt: u32, idempotent: bool, options: &RequestOptions) -> Result<Duration> {
+        let policy = options.retry.as_ref().unwrap_or(&self.config.retry);
+        if policy.should_retry(&error, attempt, idempotent) {
+            Ok(policy.delay(attempt))
+        } else {
+            Err(retry::exhausted(error, attempt))
+        }
+    }
+
+    /// Records the error a call finally returns.
+    pub fn failed(&self, error: Error, span: &RequestSpan) -> Error {
+        span.record_failure(&error);
+        error
+    }
+}
+
+fn signed_request<T>(
+    auth: &Auth,
+    interceptors: &[Arc<dyn Interceptor>],
+    url: &str,
+    payload: &T,
+    options: &RequestOptions,
+) -> Result<HttpRequest>
+where
+    T: Serialize + ?Sized,
+{
+    let json = serde_json::to_string(payload).map_err(Error::Json)?;
+    let mut request = HttpRequest {
+        method: Method::Post,
+        url: url.to_string(),
+        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+        body: json,
+        timeout: options.timeout,
+    };
+    request.headers.extend(options.headers.iter().cloned());
+    if let Some(key) = &options.idempotency_key {
+        request.headers.push(("Idempotency-Key".to_string(), key.clone()));
+    }
+    for interceptor in interceptors {
+        interceptor.before_send(&mut request)?;
+    }
+
+    sign(auth, &mut request)?;
+    Ok(request)
+}
+
+/// Adds the partner ID, timestamp and signature (or legacy `sec_key`) to the
+/// JSON body, and to the headers where the scheme defines them.
+fn sign(auth: &Auth, request: &mut HttpRequest) -> Result<()> {
+    let mut body: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&request.body)
+        .map_err(|_| Error::InvalidParameter("Request payload must be a JSON object".to_string()))?;
+
+    body.insert("partner_id".to_string(), auth.partner_id().into());
+    request
+        .headers
+        .push(("SmileID-Partner-ID".to_string(), auth.partner_id().to_string()));
+
+    match auth.scheme() {
+        SignatureScheme::Signature => {
+            let now = Utc::now();
+            let timestamp = format_timestamp(&now);
+            let signature = auth.generate_signature(&now)?;
+
+            body.insert("timestamp".to_string(), timestamp.clone().into());
+            body.insert("signature".to_string(), signature.clone().into());
+            request.headers.extend([
+                ("SmileID-Request-Signature".to_string(), signature),
+                ("SmileID-Timestamp".to_string(), timestamp),
+            ]);
+        }
+        #[cfg(feature = "legacy-sec-key")]
+        SignatureScheme::SecKey => {
+            let timestamp = Utc::now().timestamp();
+
+            body.insert("timestamp".to_string(), timestamp.into());
+            body.insert("sec_key".to_string(), auth.generate_sec_key(timestamp)?.into());
+        }
+    }
+
+    request.body = serde_json::to_string(&body).map_err(Error::Json)?;
+    Ok(())
+}
+
+/// The fields that say whether a call failed, whatever the endpoint.
+#[derive(Debug, Deserialize)]
+struct Outcome {
+    status_code: Option<u16>,
+    message: Option<String>,
+    error: Option<String>,
+    code: Option<serde_json::Value>,
+}
+
+impl Outcome {
+    /// The error text, with Smile ID's result code when there is one.
+    fn message(self) -> Option<String> {
+        let message = self.error.or(self.message)?;
+        Some(match self.code {
+            Some(serde_json::Value::String(code)) => format!("{} (code {})", message, code),
+            Some(code) if !code.is_null() => format!("{} (code {})", message, code),
+            _ => message,
+        })
+    }
+}
+
+fn parse_response<R>(response: HttpResponse, environment: &Environment) -> Result<R>
+where
+    R: DeserializeOwned,
+{
+    let outcome = serde_json::from_str::<Outcome>(&response.body);
+
+    if !(200..300).contains(&response.status) {
+        let message = outcome.ok().and_then(Outcome::message);
+        return Err(Error::Api {
+            status_code: response.status,
+            message: message.unwrap_or(response.body),
+            environment: environment.clone(),
+        });
+    }
+    if let Ok(outcome @ Outcome { status_code: Some(400..), .. }) = outcome {
+        return Err(Error::Api {
+            status_code: outcome.status_code.unwrap_or_default(),
+            message: outcome.message().unwrap_or_default(),
+            environment: environment.clone(),
+        });
+    }
+
+    let api_response: ApiResponse<R> = serde_json::from_str(&response.body).map_err(Error::Json)?;
+    Ok(api_response.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::JobStatusResponse;
+
+    #[test]
+    fn non_success_status_is_an_api_error() {
+        let err = parse_response::<JobStatusResponse>(
+            HttpResponse {
+                status: 401,
+                body: "unauthorized".to_string(),
+            },
+            &Environment::Production,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::Api { status_code: 401, .. }));
+        assert_eq!(err.environment(), Some(&Environment::Production));
+        assert!(err.to_string().ends_with("(production)"));
+    }
+
+    fn api_error(status: u16, body: &str) -> (u16, String) {
+        let response = HttpResponse {
+            status,
+            body: body.to_string(),
+        };
+        match parse_response::<JobStatusResponse>(response, &Environment::Sandbox) {
+            Err(Error::Api { status_code, message, .. }) => (status_code, message),
+            other => panic!("expected an API error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn error_bodies_are_turned_into_api_errors() {
+        assert_eq!(
+            api_error(401, r#"{"code": "2205", "error": "You are not authorized to do that."}"#),
+            (401, "You are not authorized to do that. (code 2205)".to_string())
+        );
+        assert_eq!(
+            api_error(400, r#"{"status_code": 400, "message": "Invalid id_type"}"#),
+            (400, "Invalid id_type".to_string())
+        );
+        assert_eq!(api_error(502, "<html>Bad Gateway</html>"), (502, "<html>Bad Gateway</html>".to_string()));
+        assert_eq!(
+            api_error(200, r#"{"status_code": 422, "message": "Job already exists", "code": 2215}"#),
+            (422, "Job already exists (code 2215)".to_string())
+        );
+    }
+
+    #[test]
+    fn the_envelope_is_optional_on_success() {
+        let status = parse_response::<JobStatusResponse>(
+            HttpResponse {
+                status: 200,
+                body: r#"{"code": "2301", "job_complete": false, "job_success": false}"#.to_string(),
+            },
+            &Environment::Sandbox,
+        )
+        .unwrap();
+
+        assert!(!status.job_complete);
+        assert!(status.result.is_none());
+    }
+}
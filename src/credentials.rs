@@ -0,0 +1,98 @@
+use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+
+use crate::auth::Auth;
+use crate::error::Result;
+
+/// Supplies the credentials requests are signed with and callbacks are checked against.
+///
+/// `ApiClient` asks for [`current`](Self::current) on every attempt, so a
+/// provider can swap keys without rebuilding clients or products. A plain
+/// [`Auth`] is a provider that never changes.
+pub trait CredentialProvider: Debug + Send + Sync {
+    /// The credentials to sign the next request with.
+    fn current(&self) -> Result<Auth>;
+
+    /// Every set of credentials a callback may be signed with, current first.
+    fn verification_keys(&self) -> Result<Vec<Auth>> {
+        Ok(vec![self.current()?])
+    }
+}
+
+impl CredentialProvider for Auth {
+    fn current(&self) -> Result<Auth> {
+        Ok(self.clone())
+    }
+}
+
+impl<P: CredentialProvider + ?Sized> CredentialProvider for Arc<P> {
+    fn current(&self) -> Result<Auth> {
+        (**self).current()
+    }
+
+    fn verification_keys(&self) -> Result<Vec<Auth>> {
+        (**self).verification_keys()
+    }
+}
+
+/// Credentials that can be rotated while clients and callback handlers keep running.
+///
+/// After [`rotate`](Self::rotate), requests are signed with the new key while
+/// callbacks signed with earlier keys are still accepted, until
+/// [`retire_previous`](Self::retire_previous) is called. Clones share the same keys.
+#[derive(Debug, Clone)]
+pub struct RotatingCredentials {
+    keys: Arc<RwLock<Vec<Auth>>>,
+}
+
+impl RotatingCredentials {
+    pub fn new(auth: Auth) -> Self {
+        Self {
+            keys: Arc::new(RwLock::new(vec![auth])),
+        }
+    }
+
+    /// Signs new requests with `auth`, keeping the previous keys for verification.
+    pub fn rotate(&self, auth: Auth) {
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        keys.insert(0, auth);
+    }
+
+    /// Stops accepting callbacks signed with anything but the current key.
+    pub fn retire_previous(&self) {
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        keys.truncate(1);
+    }
+}
+
+impl CredentialProvider for RotatingCredentials {
+    fn current(&self) -> Result<Auth> {
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        Ok(keys[0].clone())
+    }
+
+    fn verification_keys(&self) -> Result<Vec<Auth>> {
+        Ok(self.keys.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_changes_the_signing_key_and_keeps_old_keys_until_retired() {
+        let credentials = RotatingCredentials::new(Auth::new("old-key", "001"));
+        let shared = credentials.clone();
+
+        shared.rotate(Auth::new("new-key", "001"));
+
+        let now = chrono::Utc::now();
+        let signature = credentials.current().unwrap().generate_signature(&now).unwrap();
+        assert_eq!(signature, Auth::new("new-key", "001").generate_signature(&now).unwrap());
+        assert_eq!(credentials.verification_keys().unwrap().len(), 2);
+
+        shared.retire_previous();
+        assert_eq!(credentials.verification_keys().unwrap().len(), 1);
+    }
+}
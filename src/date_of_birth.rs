@@ -0,0 +1,263 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// The years Smile ID's four-digit date formats can carry.
+const YEARS: RangeInclusive<i32> = 1000..=9999;
+
+/// A date of birth returned by an ID authority, some of which only record the
+/// month or year.
+///
+/// Serialized the way Smile ID sends it: `YYYY-MM-DD`, `YYYY-MM` or `YYYY`.
+/// Requests take a full [`NaiveDate`] instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateOfBirth(Precision);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Precision {
+    Date(NaiveDate),
+    YearMonth { year: i32, month: u32 },
+    Year(i32),
+}
+
+impl DateOfBirth {
+    /// A full date, rejecting days that do not exist such as February 30th.
+    pub fn new(year: i32, month: u32, day: u32) -> Result<Self> {
+        check_year(year)?;
+        NaiveDate::from_ymd_opt(year, month, day)
+            .map(|date| DateOfBirth(Precision::Date(date)))
+            .ok_or_else(|| Error::InvalidParameter(format!("{:04}-{:02}-{:02} is not a valid date", year, month, day)))
+    }
+
+    pub fn year_month(year: i32, month: u32) -> Result<Self> {
+        check_year(year)?;
+        if !(1..=12).contains(&month) {
+            return Err(Error::InvalidParameter(format!("{} is not a valid month", month)));
+        }
+
+        Ok(DateOfBirth(Precision::YearMonth { year, month }))
+    }
+
+    pub fn year_only(year: i32) -> Result<Self> {
+        check_year(year)?;
+
+        Ok(DateOfBirth(Precision::Year(year)))
+    }
+
+    pub fn year(&self) -> i32 {
+        match self.0 {
+            Precision::Date(date) => date.year(),
+            Precision::YearMonth { year, .. } | Precision::Year(year) => year,
+        }
+    }
+
+    /// The month, when it is known.
+    pub fn month(&self) -> Option<u32> {
+        match self.0 {
+            Precision::Date(date) => Some(date.month()),
+            Precision::YearMonth { month, .. } => Some(month),
+            Precision::Year(_) => None,
+        }
+    }
+
+    /// The full date, when the day is known.
+    pub fn date(&self) -> Option<NaiveDate> {
+        match self.0 {
+            Precision::Date(date) => Some(date),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<NaiveDate> for DateOfBirth {
+    type Error = Error;
+
+    fn try_from(date: NaiveDate) -> Result<Self> {
+        check_date(date).map(|date| DateOfBirth(Precision::Date(date)))
+    }
+}
+
+impl FromStr for DateOfBirth {
+    type Err = Error;
+
+    /// Parses `YYYY-MM-DD`, `YYYY-MM` or `YYYY`, and the `DD/MM/YYYY` and
+    /// `DD-MM-YYYY` forms some ID authorities return.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let invalid = || Error::InvalidParameter(format!("{:?} is not a valid date of birth", value));
+
+        for format in ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y"] {
+            if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+                return DateOfBirth::try_from(date);
+            }
+        }
+
+        let number = |part: &str| part.parse().map_err(|_| invalid());
+        match value.split_once('-') {
+            Some((year, month)) if year.len() == 4 && month.len() == 2 => {
+                DateOfBirth::year_month(number(year)?, number(month)? as u32)
+            }
+            None if value.len() == 4 => DateOfBirth::year_only(number(value)?),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for DateOfBirth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Precision::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Precision::YearMonth { year, month } => write!(f, "{:04}-{:02}", year, month),
+            Precision::Year(year) => write!(f, "{:04}", year),
+        }
+    }
+}
+
+impl Serialize for DateOfBirth {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DateOfBirth {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A date of birth as an ID authority returned it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReturnedDateOfBirth {
+    Parsed(DateOfBirth),
+    /// A value in a format the crate cannot read, e.g. `31 JAN 1990`, kept as sent.
+    Unparsed(String),
+}
+
+impl ReturnedDateOfBirth {
+    pub fn parsed(&self) -> Option<&DateOfBirth> {
+        match self {
+            ReturnedDateOfBirth::Parsed(dob) => Some(dob),
+            ReturnedDateOfBirth::Unparsed(_) => None,
+        }
+    }
+}
+
+/// Reads a returned date of birth, treating blanks and `Not Available` as
+/// missing and keeping values that are not a readable date as they were sent.
+pub(crate) fn returned<'de, D>(deserializer: D) -> std::result::Result<Option<ReturnedDateOfBirth>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("Not Available") {
+        return Ok(None);
+    }
+
+    Ok(Some(match trimmed.parse() {
+        Ok(dob) => ReturnedDateOfBirth::Parsed(dob),
+        Err(_) => ReturnedDateOfBirth::Unparsed(value),
+    }))
+}
+
+/// Checks that a requested date of birth has a four-digit year.
+pub(crate) fn check_date(date: NaiveDate) -> Result<NaiveDate> {
+    check_year(date.year()).map(|()| date)
+}
+
+fn check_year(year: i32) -> Result<()> {
+    if YEARS.contains(&year) {
+        Ok(())
+    } else {
+        Err(Error::InvalidParameter(format!("{} is not a valid year of birth", year)))
+    }
+}
+
+/// Serializes a requested date of birth as `YYYY-MM-DD`.
+pub(crate) fn serialize_date<S: Serializer>(date: &NaiveDate, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(&date.format("%Y-%m-%d"))
+}
+
+pub(crate) fn serialize_optional_date<S: Serializer>(
+    date: &Option<NaiveDate>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match date {
+        Some(date) => serialize_date(date, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_dates_that_do_not_exist() {
+        assert!(DateOfBirth::new(1990, 2, 28).is_ok());
+        assert!(matches!(DateOfBirth::new(1990, 2, 30), Err(Error::InvalidParameter(_))));
+        assert!(matches!(DateOfBirth::new(1990, 13, 1), Err(Error::InvalidParameter(_))));
+        assert!(matches!(DateOfBirth::year_month(1990, 0), Err(Error::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn rejects_years_outside_four_digits() {
+        for year in [-5, 0, 999, 12345] {
+            assert!(matches!(DateOfBirth::year_only(year), Err(Error::InvalidParameter(_))), "{}", year);
+            assert!(DateOfBirth::new(year, 1, 1).is_err(), "{}", year);
+        }
+        assert!(DateOfBirth::try_from(NaiveDate::from_ymd_opt(12345, 1, 1).unwrap()).is_err());
+        assert!("0990".parse::<DateOfBirth>().is_err());
+    }
+
+    #[test]
+    fn parses_full_and_partial_dates() {
+        let full: DateOfBirth = "1990-01-31".parse().unwrap();
+
+        assert_eq!(full, DateOfBirth::new(1990, 1, 31).unwrap());
+        assert_eq!("31/01/1990".parse::<DateOfBirth>().unwrap(), full);
+        assert_eq!("1990-07".parse::<DateOfBirth>().unwrap(), DateOfBirth::year_month(1990, 7).unwrap());
+        assert_eq!("1990".parse::<DateOfBirth>().unwrap(), DateOfBirth::year_only(1990).unwrap());
+        assert!("1990-13".parse::<DateOfBirth>().is_err());
+        assert!("Not Available".parse::<DateOfBirth>().is_err());
+    }
+
+    #[test]
+    fn serializes_in_the_format_smile_id_expects() {
+        let dates = [
+            DateOfBirth::new(1990, 1, 5).unwrap(),
+            DateOfBirth::year_month(1990, 7).unwrap(),
+            DateOfBirth::year_only(1990).unwrap(),
+        ];
+
+        assert_eq!(serde_json::to_string(&dates).unwrap(), r#"["1990-01-05","1990-07","1990"]"#);
+    }
+
+    #[test]
+    fn unreadable_returned_dates_are_kept_as_sent() {
+        #[derive(Deserialize)]
+        struct Returned {
+            #[serde(default, deserialize_with = "returned")]
+            dob: Option<ReturnedDateOfBirth>,
+        }
+
+        let parse = |body: &str| serde_json::from_str::<Returned>(body).unwrap().dob;
+        let unparsed = |raw: &str| Some(ReturnedDateOfBirth::Unparsed(raw.to_string()));
+
+        assert_eq!(parse(r#"{"dob": "1990"}"#), Some(ReturnedDateOfBirth::Parsed(DateOfBirth::year_only(1990).unwrap())));
+        assert_eq!(parse(r#"{"dob": "31 JAN 1990"}"#), unparsed("31 JAN 1990"));
+        assert_eq!(parse(r#"{"dob": "1990/01/31"}"#), unparsed("1990/01/31"));
+        assert_eq!(parse(r#"{"dob": "Not Available"}"#), None);
+        assert_eq!(parse(r#"{"dob": " "}"#), None);
+        assert_eq!(parse(r#"{"dob": null}"#), None);
+        assert_eq!(parse("{}"), None);
+    }
+}
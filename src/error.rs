@@ -1,5 +1,9 @@
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::callbacks::VerificationFailure;
+use crate::config::Environment;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Error, Debug)]
@@ -7,13 +11,18 @@ pub enum Error {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
+    #[error("Transport error: {0}")]
+    Transport(String),
+
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
-    #[error("API error: {status_code} - {message}")]
+    #[error("API error: {status_code} - {message} ({environment})")]
     Api {
         status_code: u16,
         message: String,
+        /// The environment that returned the error.
+        environment: Environment,
     },
 
     #[error("Authentication error: {0}")]
@@ -23,11 +32,45 @@ pub enum Error {
     Config(String),
 
     #[error("Signature verification error: {0}")]
-    SignatureVerification(String),
+    SignatureVerification(VerificationFailure),
 
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
 
+    #[error("Request failed after {attempts} attempts: {source}")]
+    RetriesExhausted {
+        attempts: u32,
+        #[source]
+        source: Box<Error>,
+    },
+
+    #[error("Request cancelled")]
+    Cancelled,
+
+    #[error("Circuit breaker open, retry after {retry_after:?}")]
+    CircuitOpen { retry_after: Duration },
+
     #[error("Other error: {0}")]
     Other(String),
 }
+
+impl Error {
+    /// The environment the failed request was sent to, when it is known.
+    ///
+    /// API errors always carry it. HTTP errors report it when the request
+    /// went to the Smile ID sandbox or production hosts; errors raised before
+    /// sending, by a custom transport or against a custom host carry none.
+    pub fn environment(&self) -> Option<&Environment> {
+        static KNOWN: [Environment; 2] = [Environment::Sandbox, Environment::Production];
+
+        match self {
+            Error::Api { environment, .. } => Some(environment),
+            Error::Http(e) => {
+                let url = e.url()?.as_str();
+                KNOWN.iter().find(|environment| url.starts_with(environment.base_url()))
+            }
+            Error::RetriesExhausted { source, .. } => source.environment(),
+            _ => None,
+        }
+    }
+}
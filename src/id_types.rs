@@ -0,0 +1,240 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+macro_rules! string_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[doc = $doc:literal])* $variant:ident = $value:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[doc = $doc])* $variant,)*
+            /// A value this crate does not list yet. It is sent as is and
+            /// its ID numbers are not validated.
+            Other(String),
+        }
+
+        impl $name {
+            /// The value Smile ID expects in requests.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Other(value) => value,
+                }
+            }
+
+            /// Whether this is one of the values the crate lists.
+            pub fn is_known(&self) -> bool {
+                !matches!(self, $name::Other(_))
+            }
+        }
+
+        /// Parses the value Smile ID uses, ignoring case. Anything else
+        /// becomes `Other`.
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                let value = value.trim();
+                $(if value.eq_ignore_ascii_case($value) {
+                    return $name::$variant;
+                })*
+                $name::Other(value.to_string())
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                $name::from(value.as_str())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            fn from_str(value: &str) -> std::result::Result<Self, Infallible> {
+                Ok($name::from(value))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.as_str().to_string()
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                String::deserialize(deserializer).map($name::from)
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// A market Smile ID verifies IDs in, by ISO 3166-1 alpha-2 code.
+    Country {
+        Ghana = "GH",
+        Kenya = "KE",
+        Nigeria = "NG",
+        SouthAfrica = "ZA",
+        Uganda = "UG",
+    }
+}
+
+string_enum! {
+    /// A kind of ID Smile ID can verify.
+    IdType {
+        AlienCard = "ALIEN_CARD",
+        Bvn = "BVN",
+        DriversLicense = "DRIVERS_LICENSE",
+        GhanaCard = "GHANA_CARD",
+        KraPin = "KRA_PIN",
+        NationalId = "NATIONAL_ID",
+        NationalIdNoPhoto = "NATIONAL_ID_NO_PHOTO",
+        NewVoterId = "NEW_VOTER_ID",
+        Nin = "NIN",
+        NinSlip = "NIN_SLIP",
+        Passport = "PASSPORT",
+        PhoneNumber = "PHONE_NUMBER",
+        Ssnit = "SSNIT",
+        /// Nigeria's tokenised virtual NIN.
+        VirtualNin = "V_NIN",
+        VoterId = "VOTER_ID",
+    }
+}
+
+impl Country {
+    /// The ID types Smile ID verifies in this country, or none for an
+    /// `Other` country.
+    pub fn id_types(&self) -> &'static [IdType] {
+        use IdType::*;
+
+        match self {
+            Country::Ghana => &[GhanaCard, Ssnit, NewVoterId, DriversLicense, Passport],
+            Country::Kenya => &[NationalId, NationalIdNoPhoto, AlienCard, KraPin, Passport],
+            Country::Nigeria => &[Bvn, Nin, NinSlip, VirtualNin, PhoneNumber, DriversLicense, VoterId],
+            Country::SouthAfrica => &[NationalId, NationalIdNoPhoto],
+            Country::Uganda => &[NationalIdNoPhoto],
+            Country::Other(_) => &[],
+        }
+    }
+}
+
+impl IdType {
+    /// Checks that this ID type is supported in `country` and that `id_number`
+    /// has the format the ID authority issues.
+    ///
+    /// Requests with an `Other` country or ID type are left for Smile ID to
+    /// check.
+    pub fn validate(&self, country: &Country, id_number: &str) -> Result<()> {
+        if !self.is_known() || !country.is_known() {
+            return Ok(());
+        }
+        if !country.id_types().contains(self) {
+            return Err(Error::InvalidParameter(format!("{} is not supported in {}", self, country)));
+        }
+        if !self.is_well_formed(country, id_number.trim()) {
+            return Err(Error::InvalidParameter(format!(
+                "ID number does not have the {} format for {}",
+                self, country
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn is_well_formed(&self, country: &Country, number: &str) -> bool {
+        match (self, country) {
+            (IdType::Bvn | IdType::Nin | IdType::NinSlip, _) => digits(number, 11, 11),
+            (IdType::VirtualNin, _) => alphanumeric(number, 16, 16),
+            (IdType::PhoneNumber, _) => digits(number, 10, 11),
+            (IdType::GhanaCard, _) => pattern(number, "GHA-#########-#"),
+            (IdType::Ssnit, _) => pattern(number, "A############"),
+            (IdType::NewVoterId, _) => digits(number, 10, 10),
+            (IdType::KraPin, _) => pattern(number, "A#########A"),
+            (IdType::AlienCard, _) => digits(number, 6, 9),
+            (IdType::NationalId | IdType::NationalIdNoPhoto, Country::Kenya) => digits(number, 1, 9),
+            (IdType::NationalId | IdType::NationalIdNoPhoto, Country::SouthAfrica) => digits(number, 13, 13),
+            (IdType::NationalIdNoPhoto, Country::Uganda) => alphanumeric(number, 14, 14),
+            (IdType::Passport, _) => alphanumeric(number, 6, 9),
+            _ => !number.is_empty(),
+        }
+    }
+}
+
+fn digits(value: &str, min: usize, max: usize) -> bool {
+    (min..=max).contains(&value.len()) && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn alphanumeric(value: &str, min: usize, max: usize) -> bool {
+    (min..=max).contains(&value.len()) && value.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+}
+
+/// Matches `value` against `pattern`, where `#` is a digit, `A` an uppercase
+/// letter and anything else must appear as is.
+fn pattern(value: &str, pattern: &str) -> bool {
+    value.len() == pattern.len()
+        && value.bytes().zip(pattern.bytes()).all(|(b, p)| match p {
+            b'#' => b.is_ascii_digit(),
+            b'A' => b.is_ascii_uppercase(),
+            p => b == p,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate_id(country: &str, id_type: &str, id_number: &str) -> Result<()> {
+        IdType::from(id_type).validate(&Country::from(country), id_number)
+    }
+
+    #[test]
+    fn parses_codes_ignoring_case() {
+        assert_eq!("ng".parse::<Country>().unwrap(), Country::Nigeria);
+        assert_eq!("kra_pin".parse::<IdType>().unwrap(), IdType::KraPin);
+        assert_eq!(String::from(IdType::VirtualNin), "V_NIN");
+        assert_eq!("XX".parse::<Country>().unwrap(), Country::Other("XX".to_string()));
+        assert_eq!(serde_json::to_string(&IdType::from("TIN")).unwrap(), r#""TIN""#);
+    }
+
+    #[test]
+    fn unlisted_values_are_not_validated() {
+        assert!(validate_id("CI", "NATIONAL_ID", "anything").is_ok());
+        assert!(validate_id("NG", "BANK_ACCOUNT", "0123456789").is_ok());
+        assert!(!IdType::from("BANK_ACCOUNT").is_known());
+    }
+
+    #[test]
+    fn validates_id_numbers_per_country() {
+        assert!(validate_id("NG", "NIN", "12345678901").is_ok());
+        assert!(validate_id("GH", "GHANA_CARD", "GHA-123456789-0").is_ok());
+        assert!(validate_id("KE", "KRA_PIN", "A123456789B").is_ok());
+        assert!(validate_id("ZA", "NATIONAL_ID", "8001015009087").is_ok());
+        assert!(validate_id("KE", "NATIONAL_ID", "12345678").is_ok());
+
+        assert!(matches!(validate_id("NG", "NIN", "1234"), Err(Error::InvalidParameter(_))));
+        assert!(matches!(validate_id("GH", "BVN", "12345678901"), Err(Error::InvalidParameter(_))));
+        assert!(matches!(validate_id("ZA", "NATIONAL_ID", "12345678"), Err(Error::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn errors_do_not_echo_the_id_number() {
+        let err = validate_id("NG", "BVN", "2222-secret").unwrap_err();
+
+        assert!(!err.to_string().contains("2222-secret"));
+    }
+}
@@ -0,0 +1,21 @@
+use std::fmt::Debug;
+
+use crate::error::Result;
+use crate::transport::{HttpRequest, HttpResponse};
+
+/// Hooks that run around every request an [`ApiClient`](crate::ApiClient) sends.
+///
+/// Register interceptors with `ApiClient::with_interceptor`. They run once per
+/// attempt, so a retried call passes through them again. `before_send` hooks
+/// run in registration order and `after_receive` hooks in reverse order.
+pub trait Interceptor: Debug + Send + Sync {
+    /// Called before the partner ID, timestamp and signature are added to the
+    /// headers and JSON body. Returning an error aborts the attempt without
+    /// sending it.
+    fn before_send(&self, _request: &mut HttpRequest) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the signed request and whatever the transport returned.
+    fn after_receive(&self, _request: &HttpRequest, _response: &Result<HttpResponse>) {}
+}
@@ -1,24 +1,76 @@
+mod actions;
 mod api;
 mod auth;
+pub mod callbacks;
+mod circuit_breaker;
 mod config;
+mod core;
+mod credentials;
+mod date_of_birth;
 mod error;
+mod id_types;
+mod interceptor;
 mod models;
+mod options;
 mod products;
-mod utils;
+mod rate_limit;
+mod result_code;
+mod retry;
+mod secret;
+mod services;
+mod telemetry;
+mod tenants;
+pub mod transport;
+pub mod utils;
 
+pub use actions::{ActionResult, Actions};
 pub use api::ApiClient;
-pub use auth::Auth;
-pub use config::Config;
+pub use auth::{Auth, SignatureScheme};
+pub use callbacks::{CallbackHandler, CallbackVerifier, VerificationFailure};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState, CircuitPermit};
+pub use config::{Config, Environment};
+pub use credentials::{CredentialProvider, RotatingCredentials};
+pub use date_of_birth::{DateOfBirth, ReturnedDateOfBirth};
 pub use error::{Error, Result};
+pub use id_types::{Country, IdType};
+pub use interceptor::Interceptor;
 pub use models::*;
+pub use options::{CancellationToken, Cancelled, RequestOptions};
 pub use products::*;
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use result_code::{ResultCode, ResultOutcome};
+pub use retry::RetryPolicy;
+pub use secret::ApiKey;
+pub use services::{CountryServices, IdTypeService, Services};
+pub use tenants::TenantRegistry;
+pub use transport::{
+    ClientIdentity, HttpOptions, HttpRequest, HttpResponse, Method, ProxyConfig, ReqwestTransport, Transport,
+};
+
+/// Blocking versions of the client and products, sharing the async request core.
+#[cfg(feature = "blocking")]
+pub mod blocking {
+    pub use crate::api::blocking::ApiClient;
+    pub use crate::products::blocking::*;
+    pub use crate::transport::blocking::{ReqwestTransport, Transport};
+}
 
 pub mod prelude {
     pub use crate::api::ApiClient;
-    pub use crate::auth::Auth;
-    pub use crate::config::Config;
+    pub use crate::auth::{Auth, SignatureScheme};
+    pub use crate::callbacks::{CallbackHandler, CallbackVerifier};
+    pub use crate::circuit_breaker::CircuitBreakerConfig;
+    pub use crate::config::{Config, Environment};
+    pub use crate::credentials::{CredentialProvider, RotatingCredentials};
+    pub use crate::date_of_birth::{DateOfBirth, ReturnedDateOfBirth};
     pub use crate::error::{Error, Result};
+    pub use crate::interceptor::Interceptor;
+    pub use crate::options::{CancellationToken, RequestOptions};
     pub use crate::products::*;
+    pub use crate::rate_limit::RateLimit;
+    pub use crate::result_code::{ResultCode, ResultOutcome};
+    pub use crate::retry::RetryPolicy;
+    pub use crate::transport::Transport;
 }
 
 #[cfg(test)]
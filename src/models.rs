@@ -1,60 +1,140 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 
+use crate::actions::Actions;
+use crate::callbacks::PartnerParams;
+use crate::date_of_birth::{self, ReturnedDateOfBirth};
+use crate::id_types::{Country, IdType};
+use crate::result_code::ResultCode;
+
+/// The envelope around every response body.
+///
+/// `status_code` and `message` are only present on some endpoints; the
+/// endpoint's own fields are flattened into `data`.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ApiResponse<T> {
 
-    pub status_code: u16,
-    pub message: String,
+    pub status_code: Option<u16>,
+    pub message: Option<String>,
 
     #[serde(flatten)]
     pub data: T,
 }
 
+/// The status of a job, as returned by `job_status`.
+///
+/// `result` is only present once Smile ID has a result for the job, and
+/// `history` and `image_links` only when they were asked for.
 #[derive(Debug, Clone, Deserialize)]
 pub struct JobStatusResponse {
-    pub job_id: String,
-    pub job_status: JobStatus,
-    pub job_type: String,
+    /// The status code, e.g. `2302` once the job is complete.
+    pub code: Option<ResultCode>,
     pub job_complete: bool,
     pub job_success: bool,
-    pub result_type: Option<String>,
-    pub result_text: Option<String>,
-    pub result_code: Option<String>,
-    pub actions: Option<Vec<String>>,
-    pub confidence_values: Option<HashMap<String, f64>>,
-    pub history: Option<Vec<JobHistoryItem>>,
+    pub result: Option<JobStatusResult>,
+    pub history: Option<Vec<JobStatusResult>>,
     pub image_links: Option<HashMap<String, String>>,
+    pub signature: Option<String>,
+    pub timestamp: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum JobStatus {
-    Pending,
-    InProgress,
-    Completed,
-    Failed,
+impl JobStatusResponse {
+    /// The result code of the job's result, when it has one.
+    pub fn result_code(&self) -> Option<&ResultCode> {
+        self.result.as_ref()?.result_code.as_ref()
+    }
+
+    /// The checks reported with the job's result, when it has one.
+    pub fn actions(&self) -> Option<&Actions> {
+        self.result.as_ref().map(|result| &result.actions)
+    }
 }
 
+/// A job result as `job_status` reports it, both for the current result and
+/// each entry of the history.
+///
+/// Fields without a typed counterpart, such as `ConfidenceValue` or
+/// `IsFinalResult`, are kept in `fields`.
 #[derive(Debug, Clone, Deserialize)]
-pub struct JobHistoryItem {
-    pub job_id: String,
-    pub job_type: String,
-    pub job_status: JobStatus,
-    pub job_complete: bool,
-    pub job_success: bool,
+#[serde(rename_all = "PascalCase")]
+pub struct JobStatusResult {
+    #[serde(rename = "SmileJobID")]
+    pub smile_job_id: Option<String>,
+    pub partner_params: Option<PartnerParams>,
     pub result_type: Option<String>,
     pub result_text: Option<String>,
-    pub result_code: Option<String>,
-    pub timestamp: String,
+    pub result_code: Option<ResultCode>,
+    #[serde(default)]
+    pub actions: Actions,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+/// What Smile ID returns when a job is submitted.
+///
+/// Jobs that finish synchronously also carry their result; anything the
+/// product returns beyond these fields is kept in `fields`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmissionResponse {
+    /// Only sent at the top level of asynchronous submissions; see [`job_id`](Self::job_id).
+    job_id: Option<String>,
+    /// The identifiers a synchronous result was submitted with.
+    #[serde(rename = "PartnerParams", alias = "partner_params")]
+    pub partner_params: Option<PartnerParams>,
+    #[serde(rename = "SmileJobID", alias = "smile_job_id")]
+    pub smile_job_id: Option<String>,
+    #[serde(rename = "ResultType", alias = "result_type")]
+    pub result_type: Option<String>,
+    #[serde(rename = "ResultText", alias = "result_text")]
+    pub result_text: Option<String>,
+    #[serde(rename = "ResultCode", alias = "result_code")]
+    pub result_code: Option<ResultCode>,
+    #[serde(rename = "Actions", alias = "actions", default)]
+    pub actions: Actions,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl SubmissionResponse {
+    /// The job ID, from the top level or from `PartnerParams`, whichever the
+    /// endpoint returned.
+    pub fn job_id(&self) -> Option<&str> {
+        self.job_id
+            .as_deref()
+            .or_else(|| self.partner_params.as_ref().map(|params| params.job_id.as_str()))
+    }
+}
+
+/// An Enhanced KYC submission, with the identity details the ID authority returned.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnhancedKycResponse {
+    #[serde(rename = "FullName", alias = "full_name")]
+    pub full_name: Option<String>,
+    /// Missing when the ID authority returned no date or `Not Available`.
+    #[serde(rename = "DOB", alias = "dob", default, deserialize_with = "date_of_birth::returned")]
+    pub dob: Option<ReturnedDateOfBirth>,
+    #[serde(rename = "Gender", alias = "gender")]
+    pub gender: Option<String>,
+    #[serde(rename = "PhoneNumber", alias = "phone_number")]
+    pub phone_number: Option<String>,
+    #[serde(rename = "Address", alias = "address")]
+    pub address: Option<String>,
+    #[serde(rename = "IDNumber", alias = "id_number")]
+    pub id_number: Option<String>,
+    #[serde(rename = "Photo", alias = "photo")]
+    pub photo: Option<String>,
+    #[serde(flatten)]
+    pub submission: SubmissionResponse,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct BasicKycRequest {
 
-    pub id_type: String,
+    pub id_type: IdType,
     pub id_number: String,
-    pub country: String,
+    pub country: Country,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub first_name: Option<String>,
@@ -62,8 +142,8 @@ pub struct BasicKycRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_name: Option<String>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub dob: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "date_of_birth::serialize_optional_date")]
+    pub dob: Option<NaiveDate>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub partner_params: Option<HashMap<String, String>>,
@@ -72,12 +152,13 @@ pub struct BasicKycRequest {
 #[derive(Debug, Clone, Serialize)]
 pub struct EnhancedKycRequest {
 
-    pub id_type: String,
+    pub id_type: IdType,
     pub id_number: String,
-    pub country: String,
+    pub country: Country,
     pub first_name: String,
     pub last_name: String,
-    pub dob: String,
+    #[serde(serialize_with = "date_of_birth::serialize_date")]
+    pub dob: NaiveDate,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub partner_params: Option<HashMap<String, String>>,
@@ -86,12 +167,13 @@ pub struct EnhancedKycRequest {
 #[derive(Debug, Clone, Serialize)]
 pub struct BiometricKycRequest {
 
-    pub id_type: String,
+    pub id_type: IdType,
     pub id_number: String,
-    pub country: String,
+    pub country: Country,
     pub first_name: String,
     pub last_name: String,
-    pub dob: String,
+    #[serde(serialize_with = "date_of_birth::serialize_date")]
+    pub dob: NaiveDate,
     pub selfie_image: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::retry::RetryPolicy;
+
+/// Settings for a single call that override the client's defaults.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Replaces `Config::timeout` for each attempt of this call.
+    pub timeout: Option<Duration>,
+    /// Extra headers sent with every attempt, before interceptors run.
+    pub headers: Vec<(String, String)>,
+    /// Sent as the `Idempotency-Key` header, unchanged across retries.
+    pub idempotency_key: Option<String>,
+    /// Replaces `Config::retry` for this call.
+    pub retry: Option<RetryPolicy>,
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+/// Cancels the calls it was passed to, from any thread or task.
+///
+/// Async calls stop immediately, even mid-request. Blocking calls stop
+/// before their next attempt and during backoff and rate-limit waits; a
+/// request already sent is allowed to finish.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Wakers>,
+    /// Notified with `wakers` locked, for blocking waits.
+    condvar: Condvar,
+}
+
+/// The wakers of pending [`Cancelled`] futures, keyed so each future can
+/// deregister itself when dropped.
+#[derive(Debug, Default)]
+struct Wakers {
+    next_key: u64,
+    registered: HashMap<u64, Waker>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let wakers = {
+            let mut wakers = self.wakers();
+            self.inner.condvar.notify_all();
+            std::mem::take(&mut wakers.registered)
+        };
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once [`cancel`](Self::cancel) has been called.
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled { token: self, key: None }
+    }
+
+    /// Blocks the current thread for `duration`, or until the token is cancelled.
+    #[cfg(any(feature = "blocking", test))]
+    pub(crate) fn sleep_blocking(&self, duration: Duration) -> Result<()> {
+        let wakers = self.wakers();
+        let _wakers = self
+            .inner
+            .condvar
+            .wait_timeout_while(wakers, duration, |_| !self.is_cancelled())
+            .unwrap_or_else(|e| e.into_inner());
+
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn wakers(&self) -> MutexGuard<'_, Wakers> {
+        self.inner.wakers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+#[derive(Debug)]
+pub struct Cancelled<'a> {
+    token: &'a CancellationToken,
+    key: Option<u64>,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if this.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = this.token.wakers();
+        let key = *this.key.get_or_insert_with(|| {
+            wakers.next_key += 1;
+            wakers.next_key
+        });
+        match wakers.registered.get(&key) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            _ => {
+                wakers.registered.insert(key, cx.waker().clone());
+            }
+        }
+        drop(wakers);
+
+        // Re-check so a cancel between the load and the insert is not missed.
+        if this.token.is_cancelled() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Cancelled<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            self.token.wakers().registered.remove(&key);
+        }
+    }
+}
+
+/// Runs `future` to completion unless the call is cancelled first.
+pub(crate) async fn cancellable<F: Future>(future: F, options: &RequestOptions) -> Result<F::Output> {
+    let Some(token) = &options.cancellation else {
+        return Ok(future.await);
+    };
+
+    let mut future = pin!(future);
+    let mut cancelled = pin!(token.cancelled());
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        cancelled.as_mut().poll(cx).map(|()| Err(Error::Cancelled))
+    })
+    .await
+}
+
+/// Blocks the current thread for `duration` unless the call is cancelled first.
+#[cfg(feature = "blocking")]
+pub(crate) fn sleep_blocking(duration: Duration, options: &RequestOptions) -> Result<()> {
+    match &options.cancellation {
+        Some(token) => token.sleep_blocking(duration),
+        None => {
+            std::thread::sleep(duration);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelling_wakes_pending_futures() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            canceller.cancel();
+        });
+        tokio_test::block_on(token.cancelled());
+        handle.join().unwrap();
+
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn dropped_futures_deregister_their_wakers() {
+        let token = CancellationToken::new();
+
+        for _ in 0..3 {
+            let mut cancelled = pin!(token.cancelled());
+            let pending = tokio_test::block_on(poll_fn(|cx| Poll::Ready(cancelled.as_mut().poll(cx).is_pending())));
+            assert!(pending);
+            assert_eq!(token.wakers().registered.len(), 1);
+        }
+
+        assert!(token.wakers().registered.is_empty());
+    }
+
+    #[test]
+    fn blocking_sleeps_stop_when_cancelled() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            canceller.cancel();
+        });
+        let started = std::time::Instant::now();
+        let slept = token.sleep_blocking(Duration::from_secs(60));
+        handle.join().unwrap();
+
+        assert!(matches!(slept, Err(Error::Cancelled)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(token.sleep_blocking(Duration::from_secs(60)).is_err());
+    }
+}
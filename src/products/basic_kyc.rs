@@ -1,8 +1,12 @@
-use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
 
 use crate::api::ApiClient;
+use crate::core::Call;
+use crate::date_of_birth;
 use crate::error::Result;
-use crate::models::{ApiResponse, BasicKycRequest};
+use crate::id_types::{Country, IdType};
+use crate::models::{BasicKycRequest, SubmissionResponse};
+use crate::options::RequestOptions;
 
 #[derive(Debug, Clone)]
 pub struct BasicKyc {
@@ -13,76 +17,119 @@ impl BasicKyc {
     pub fn new(client: ApiClient) -> Self {
         Self { client }
     }
-    
+
     pub async fn verify(
         &self,
-        id_type: impl Into<String>,
+        id_type: impl Into<IdType>,
         id_number: impl Into<String>,
-        country: impl Into<String>,
+        country: impl Into<Country>,
         first_name: Option<String>,
         last_name: Option<String>,
-        dob: Option<String>,
-    ) -> Result<String> {
-        let request = BasicKycRequest {
-            id_type: id_type.into(),
-            id_number: id_number.into(),
-            country: country.into(),
+        dob: Option<NaiveDate>,
+    ) -> Result<SubmissionResponse> {
+        self.verify_with_options(
+            id_type,
+            id_number,
+            country,
             first_name,
             last_name,
             dob,
-            partner_params: None,
-        };
-        
-        let url = format!("{}/basic_kyc", self.client.base_url());
-        let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request).await?;
-        
-        Ok(response.data.job_id)
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn verify_with_options(
+        &self,
+        id_type: impl Into<IdType>,
+        id_number: impl Into<String>,
+        country: impl Into<Country>,
+        first_name: Option<String>,
+        last_name: Option<String>,
+        dob: Option<NaiveDate>,
+        options: &RequestOptions,
+    ) -> Result<SubmissionResponse> {
+        let call = call(id_type, id_number, country, first_name, last_name, dob)?;
+        self.client.execute(call, options).await
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct VerifyResponse {
-    pub job_id: String,
+fn call(
+    id_type: impl Into<IdType>,
+    id_number: impl Into<String>,
+    country: impl Into<Country>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    dob: Option<NaiveDate>,
+) -> Result<Call<BasicKycRequest>> {
+    let (id_type, id_number, country) = (id_type.into(), id_number.into().trim().to_string(), country.into());
+    id_type.validate(&country, &id_number)?;
+
+    Ok(Call {
+        path: "basic_kyc",
+        idempotent: false,
+        payload: BasicKycRequest {
+            id_type,
+            id_number,
+            country,
+            first_name,
+            last_name,
+            dob: dob.map(date_of_birth::check_date).transpose()?,
+            partner_params: None,
+        },
+    })
 }
 
 #[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
     use crate::api::blocking::ApiClient;
-    
+
+    /// Blocking counterpart of [`BasicKyc`](super::BasicKyc).
     #[derive(Debug, Clone)]
     pub struct BasicKyc {
         client: ApiClient,
     }
-    
+
     impl BasicKyc {
         pub fn new(client: ApiClient) -> Self {
             Self { client }
         }
-        
+
         pub fn verify(
             &self,
-            id_type: impl Into<String>,
+            id_type: impl Into<IdType>,
             id_number: impl Into<String>,
-            country: impl Into<String>,
+            country: impl Into<Country>,
             first_name: Option<String>,
             last_name: Option<String>,
-            dob: Option<String>,
-        ) -> Result<String> {
-            let request = BasicKycRequest {
-                id_type: id_type.into(),
-                id_number: id_number.into(),
-                country: country.into(),
+            dob: Option<NaiveDate>,
+        ) -> Result<SubmissionResponse> {
+            self.verify_with_options(
+                id_type,
+                id_number,
+                country,
                 first_name,
                 last_name,
                 dob,
-                partner_params: None,
-            };
-            
-            let url = format!("{}/basic_kyc", self.client.base_url());
-            let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request)?;
-            
-            Ok(response.data.job_id)
+                &RequestOptions::default(),
+            )
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        pub fn verify_with_options(
+            &self,
+            id_type: impl Into<IdType>,
+            id_number: impl Into<String>,
+            country: impl Into<Country>,
+            first_name: Option<String>,
+            last_name: Option<String>,
+            dob: Option<NaiveDate>,
+            options: &RequestOptions,
+        ) -> Result<SubmissionResponse> {
+            let call = call(id_type, id_number, country, first_name, last_name, dob)?;
+            self.client.execute(call, options)
         }
     }
 }
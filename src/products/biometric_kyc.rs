@@ -1,8 +1,12 @@
-use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
 
 use crate::api::ApiClient;
+use crate::core::Call;
+use crate::date_of_birth;
 use crate::error::Result;
-use crate::models::{ApiResponse, BiometricKycRequest};
+use crate::id_types::{Country, IdType};
+use crate::models::{BiometricKycRequest, SubmissionResponse};
+use crate::options::RequestOptions;
 
 #[derive(Debug, Clone)]
 pub struct BiometricKyc {
@@ -13,81 +17,129 @@ impl BiometricKyc {
     pub fn new(client: ApiClient) -> Self {
         Self { client }
     }
-    
-    
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn verify(
         &self,
-        id_type: impl Into<String>,
+        id_type: impl Into<IdType>,
         id_number: impl Into<String>,
-        country: impl Into<String>,
+        country: impl Into<Country>,
         first_name: impl Into<String>,
         last_name: impl Into<String>,
-        dob: impl Into<String>,
+        dob: NaiveDate,
         selfie_image: impl Into<String>,
-    ) -> Result<String> {
-        let request = BiometricKycRequest {
-            id_type: id_type.into(),
-            id_number: id_number.into(),
-            country: country.into(),
+    ) -> Result<SubmissionResponse> {
+        self.verify_with_options(
+            id_type,
+            id_number,
+            country,
+            first_name,
+            last_name,
+            dob,
+            selfie_image,
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn verify_with_options(
+        &self,
+        id_type: impl Into<IdType>,
+        id_number: impl Into<String>,
+        country: impl Into<Country>,
+        first_name: impl Into<String>,
+        last_name: impl Into<String>,
+        dob: NaiveDate,
+        selfie_image: impl Into<String>,
+        options: &RequestOptions,
+    ) -> Result<SubmissionResponse> {
+        let call = call(id_type, id_number, country, first_name, last_name, dob, selfie_image)?;
+        self.client.execute(call, options).await
+    }
+}
+
+fn call(
+    id_type: impl Into<IdType>,
+    id_number: impl Into<String>,
+    country: impl Into<Country>,
+    first_name: impl Into<String>,
+    last_name: impl Into<String>,
+    dob: NaiveDate,
+    selfie_image: impl Into<String>,
+) -> Result<Call<BiometricKycRequest>> {
+    let (id_type, id_number, country) = (id_type.into(), id_number.into().trim().to_string(), country.into());
+    id_type.validate(&country, &id_number)?;
+
+    Ok(Call {
+        path: "biometric_kyc",
+        idempotent: false,
+        payload: BiometricKycRequest {
+            id_type,
+            id_number,
+            country,
             first_name: first_name.into(),
             last_name: last_name.into(),
-            dob: dob.into(),
+            dob: date_of_birth::check_date(dob)?,
             selfie_image: selfie_image.into(),
             partner_params: None,
-        };
-        
-        let url = format!("{}/biometric_kyc", self.client.base_url());
-        let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request).await?;
-        
-        Ok(response.data.job_id)
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct VerifyResponse {
-    pub job_id: String,
+        },
+    })
 }
 
 #[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
     use crate::api::blocking::ApiClient;
-    
+
+    /// Blocking counterpart of [`BiometricKyc`](super::BiometricKyc).
     #[derive(Debug, Clone)]
     pub struct BiometricKyc {
         client: ApiClient,
     }
-    
+
     impl BiometricKyc {
         pub fn new(client: ApiClient) -> Self {
             Self { client }
         }
-        
+
+        #[allow(clippy::too_many_arguments)]
         pub fn verify(
             &self,
-            id_type: impl Into<String>,
+            id_type: impl Into<IdType>,
+            id_number: impl Into<String>,
+            country: impl Into<Country>,
+            first_name: impl Into<String>,
+            last_name: impl Into<String>,
+            dob: NaiveDate,
+            selfie_image: impl Into<String>,
+        ) -> Result<SubmissionResponse> {
+            self.verify_with_options(
+                id_type,
+                id_number,
+                country,
+                first_name,
+                last_name,
+                dob,
+                selfie_image,
+                &RequestOptions::default(),
+            )
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        pub fn verify_with_options(
+            &self,
+            id_type: impl Into<IdType>,
             id_number: impl Into<String>,
-            country: impl Into<String>,
+            country: impl Into<Country>,
             first_name: impl Into<String>,
             last_name: impl Into<String>,
-            dob: impl Into<String>,
+            dob: NaiveDate,
             selfie_image: impl Into<String>,
-        ) -> Result<String> {
-            let request = BiometricKycRequest {
-                id_type: id_type.into(),
-                id_number: id_number.into(),
-                country: country.into(),
-                first_name: first_name.into(),
-                last_name: last_name.into(),
-                dob: dob.into(),
-                selfie_image: selfie_image.into(),
-                partner_params: None,
-            };
-            
-            let url = format!("{}/biometric_kyc", self.client.base_url());
-            let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request)?;
-            
-            Ok(response.data.job_id)
+            options: &RequestOptions,
+        ) -> Result<SubmissionResponse> {
+            let call = call(id_type, id_number, country, first_name, last_name, dob, selfie_image)?;
+            self.client.execute(call, options)
         }
     }
 }
@@ -1,10 +1,9 @@
-use serde::{Deserialize, Serialize};
-
 use crate::api::ApiClient;
+use crate::core::Call;
 use crate::error::Result;
-use crate::models::{ApiResponse, BusinessVerificationRequest};
+use crate::models::{BusinessVerificationRequest, SubmissionResponse};
+use crate::options::RequestOptions;
 
-/// Business Verification product
 #[derive(Debug, Clone)]
 pub struct BusinessVerification {
     client: ApiClient,
@@ -14,64 +13,90 @@ impl BusinessVerification {
     pub fn new(client: ApiClient) -> Self {
         Self { client }
     }
-    
+
     pub async fn verify(
         &self,
         business_name: impl Into<String>,
         registration_number: impl Into<String>,
         country: impl Into<String>,
-    ) -> Result<String> {
-        let request = BusinessVerificationRequest {
+    ) -> Result<SubmissionResponse> {
+        self.verify_with_options(
+            business_name,
+            registration_number,
+            country,
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    pub async fn verify_with_options(
+        &self,
+        business_name: impl Into<String>,
+        registration_number: impl Into<String>,
+        country: impl Into<String>,
+        options: &RequestOptions,
+    ) -> Result<SubmissionResponse> {
+        let call = call(business_name, registration_number, country);
+        self.client.execute(call, options).await
+    }
+}
+
+fn call(
+    business_name: impl Into<String>,
+    registration_number: impl Into<String>,
+    country: impl Into<String>,
+) -> Call<BusinessVerificationRequest> {
+    Call {
+        path: "business_verification",
+        idempotent: false,
+        payload: BusinessVerificationRequest {
             business_name: business_name.into(),
             registration_number: registration_number.into(),
             country: country.into(),
             partner_params: None,
-        };
-        
-        let url = format!("{}/business_verification", self.client.base_url());
-        let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request).await?;
-        
-        Ok(response.data.job_id)
+        },
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct VerifyResponse {
-    pub job_id: String,
-}
-
 #[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
     use crate::api::blocking::ApiClient;
-    
+
+    /// Blocking counterpart of [`BusinessVerification`](super::BusinessVerification).
     #[derive(Debug, Clone)]
     pub struct BusinessVerification {
         client: ApiClient,
     }
-    
+
     impl BusinessVerification {
         pub fn new(client: ApiClient) -> Self {
             Self { client }
         }
-        
+
         pub fn verify(
             &self,
             business_name: impl Into<String>,
             registration_number: impl Into<String>,
             country: impl Into<String>,
-        ) -> Result<String> {
-            let request = BusinessVerificationRequest {
-                business_name: business_name.into(),
-                registration_number: registration_number.into(),
-                country: country.into(),
-                partner_params: None,
-            };
-            
-            let url = format!("{}/business_verification", self.client.base_url());
-            let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request)?;
-            
-            Ok(response.data.job_id)
+        ) -> Result<SubmissionResponse> {
+            self.verify_with_options(
+                business_name,
+                registration_number,
+                country,
+                &RequestOptions::default(),
+            )
+        }
+
+        pub fn verify_with_options(
+            &self,
+            business_name: impl Into<String>,
+            registration_number: impl Into<String>,
+            country: impl Into<String>,
+            options: &RequestOptions,
+        ) -> Result<SubmissionResponse> {
+            let call = call(business_name, registration_number, country);
+            self.client.execute(call, options)
         }
     }
 }
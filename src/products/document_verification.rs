@@ -1,8 +1,8 @@
-use serde::{Deserialize, Serialize};
-
 use crate::api::ApiClient;
+use crate::core::Call;
 use crate::error::Result;
-use crate::models::{ApiResponse, DocumentVerificationRequest};
+use crate::models::{DocumentVerificationRequest, SubmissionResponse};
+use crate::options::RequestOptions;
 
 #[derive(Debug, Clone)]
 pub struct DocumentVerification {
@@ -13,64 +13,90 @@ impl DocumentVerification {
     pub fn new(client: ApiClient) -> Self {
         Self { client }
     }
-    
+
     pub async fn verify(
         &self,
         document_type: impl Into<String>,
         country: impl Into<String>,
         document_images: Vec<String>,
-    ) -> Result<String> {
-        let request = DocumentVerificationRequest {
+    ) -> Result<SubmissionResponse> {
+        self.verify_with_options(
+            document_type,
+            country,
+            document_images,
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    pub async fn verify_with_options(
+        &self,
+        document_type: impl Into<String>,
+        country: impl Into<String>,
+        document_images: Vec<String>,
+        options: &RequestOptions,
+    ) -> Result<SubmissionResponse> {
+        let call = call(document_type, country, document_images);
+        self.client.execute(call, options).await
+    }
+}
+
+fn call(
+    document_type: impl Into<String>,
+    country: impl Into<String>,
+    document_images: Vec<String>,
+) -> Call<DocumentVerificationRequest> {
+    Call {
+        path: "document_verification",
+        idempotent: false,
+        payload: DocumentVerificationRequest {
             document_type: document_type.into(),
             country: country.into(),
             document_images,
             partner_params: None,
-        };
-        
-        let url = format!("{}/document_verification", self.client.base_url());
-        let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request).await?;
-        
-        Ok(response.data.job_id)
+        },
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct VerifyResponse {
-    pub job_id: String,
-}
-
 #[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
     use crate::api::blocking::ApiClient;
-    
+
+    /// Blocking counterpart of [`DocumentVerification`](super::DocumentVerification).
     #[derive(Debug, Clone)]
     pub struct DocumentVerification {
         client: ApiClient,
     }
-    
+
     impl DocumentVerification {
         pub fn new(client: ApiClient) -> Self {
             Self { client }
         }
-        
+
         pub fn verify(
             &self,
             document_type: impl Into<String>,
             country: impl Into<String>,
             document_images: Vec<String>,
-        ) -> Result<String> {
-            let request = DocumentVerificationRequest {
-                document_type: document_type.into(),
-                country: country.into(),
+        ) -> Result<SubmissionResponse> {
+            self.verify_with_options(
+                document_type,
+                country,
                 document_images,
-                partner_params: None,
-            };
-            
-            let url = format!("{}/document_verification", self.client.base_url());
-            let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request)?;
-            
-            Ok(response.data.job_id)
+                &RequestOptions::default(),
+            )
+        }
+
+        pub fn verify_with_options(
+            &self,
+            document_type: impl Into<String>,
+            country: impl Into<String>,
+            document_images: Vec<String>,
+            options: &RequestOptions,
+        ) -> Result<SubmissionResponse> {
+            let call = call(document_type, country, document_images);
+            self.client.execute(call, options)
         }
     }
 }
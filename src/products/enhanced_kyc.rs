@@ -1,8 +1,12 @@
-use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
 
 use crate::api::ApiClient;
+use crate::core::Call;
+use crate::date_of_birth;
 use crate::error::Result;
-use crate::models::{ApiResponse, EnhancedKycRequest};
+use crate::id_types::{Country, IdType};
+use crate::models::{EnhancedKycRequest, EnhancedKycResponse};
+use crate::options::RequestOptions;
 
 #[derive(Debug, Clone)]
 pub struct EnhancedKyc {
@@ -13,76 +17,119 @@ impl EnhancedKyc {
     pub fn new(client: ApiClient) -> Self {
         Self { client }
     }
-    
+
     pub async fn verify(
         &self,
-        id_type: impl Into<String>,
+        id_type: impl Into<IdType>,
         id_number: impl Into<String>,
-        country: impl Into<String>,
+        country: impl Into<Country>,
         first_name: impl Into<String>,
         last_name: impl Into<String>,
-        dob: impl Into<String>,
-    ) -> Result<String> {
-        let request = EnhancedKycRequest {
-            id_type: id_type.into(),
-            id_number: id_number.into(),
-            country: country.into(),
-            first_name: first_name.into(),
-            last_name: last_name.into(),
-            dob: dob.into(),
-            partner_params: None,
-        };
-        
-        let url = format!("{}/enhanced_kyc", self.client.base_url());
-        let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request).await?;
-        
-        Ok(response.data.job_id)
+        dob: NaiveDate,
+    ) -> Result<EnhancedKycResponse> {
+        self.verify_with_options(
+            id_type,
+            id_number,
+            country,
+            first_name,
+            last_name,
+            dob,
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn verify_with_options(
+        &self,
+        id_type: impl Into<IdType>,
+        id_number: impl Into<String>,
+        country: impl Into<Country>,
+        first_name: impl Into<String>,
+        last_name: impl Into<String>,
+        dob: NaiveDate,
+        options: &RequestOptions,
+    ) -> Result<EnhancedKycResponse> {
+        let call = call(id_type, id_number, country, first_name, last_name, dob)?;
+        self.client.execute(call, options).await
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct VerifyResponse {
-    pub job_id: String,
+fn call(
+    id_type: impl Into<IdType>,
+    id_number: impl Into<String>,
+    country: impl Into<Country>,
+    first_name: impl Into<String>,
+    last_name: impl Into<String>,
+    dob: NaiveDate,
+) -> Result<Call<EnhancedKycRequest>> {
+    let (id_type, id_number, country) = (id_type.into(), id_number.into().trim().to_string(), country.into());
+    id_type.validate(&country, &id_number)?;
+
+    Ok(Call {
+        path: "enhanced_kyc",
+        idempotent: false,
+        payload: EnhancedKycRequest {
+            id_type,
+            id_number,
+            country,
+            first_name: first_name.into(),
+            last_name: last_name.into(),
+            dob: date_of_birth::check_date(dob)?,
+            partner_params: None,
+        },
+    })
 }
 
 #[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
     use crate::api::blocking::ApiClient;
-    
+
+    /// Blocking counterpart of [`EnhancedKyc`](super::EnhancedKyc).
     #[derive(Debug, Clone)]
     pub struct EnhancedKyc {
         client: ApiClient,
     }
-    
+
     impl EnhancedKyc {
         pub fn new(client: ApiClient) -> Self {
             Self { client }
         }
-        
+
         pub fn verify(
             &self,
-            id_type: impl Into<String>,
+            id_type: impl Into<IdType>,
+            id_number: impl Into<String>,
+            country: impl Into<Country>,
+            first_name: impl Into<String>,
+            last_name: impl Into<String>,
+            dob: NaiveDate,
+        ) -> Result<EnhancedKycResponse> {
+            self.verify_with_options(
+                id_type,
+                id_number,
+                country,
+                first_name,
+                last_name,
+                dob,
+                &RequestOptions::default(),
+            )
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        pub fn verify_with_options(
+            &self,
+            id_type: impl Into<IdType>,
             id_number: impl Into<String>,
-            country: impl Into<String>,
+            country: impl Into<Country>,
             first_name: impl Into<String>,
             last_name: impl Into<String>,
-            dob: impl Into<String>,
-        ) -> Result<String> {
-            let request = EnhancedKycRequest {
-                id_type: id_type.into(),
-                id_number: id_number.into(),
-                country: country.into(),
-                first_name: first_name.into(),
-                last_name: last_name.into(),
-                dob: dob.into(),
-                partner_params: None,
-            };
-            
-            let url = format!("{}/enhanced_kyc", self.client.base_url());
-            let response: ApiResponse<VerifyResponse> = self.client.post(&url, &request)?;
-            
-            Ok(response.data.job_id)
+            dob: NaiveDate,
+            options: &RequestOptions,
+        ) -> Result<EnhancedKycResponse> {
+            let call = call(id_type, id_number, country, first_name, last_name, dob)?;
+            self.client.execute(call, options)
         }
     }
 }
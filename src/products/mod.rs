@@ -11,3 +11,240 @@ pub use biometric_kyc::BiometricKyc;
 pub use document_verification::DocumentVerification;
 pub use smartselfie_auth::SmartSelfieAuth;
 pub use business_verification::BusinessVerification;
+
+#[cfg(feature = "blocking")]
+pub mod blocking {
+    pub use super::basic_kyc::blocking::BasicKyc;
+    pub use super::enhanced_kyc::blocking::EnhancedKyc;
+    pub use super::biometric_kyc::blocking::BiometricKyc;
+    pub use super::document_verification::blocking::DocumentVerification;
+    pub use super::smartselfie_auth::blocking::SmartSelfieAuth;
+    pub use super::business_verification::blocking::BusinessVerification;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::ActionResult;
+    use crate::api::ApiClient;
+    use crate::config::Config;
+    use crate::date_of_birth::{DateOfBirth, ReturnedDateOfBirth};
+    use crate::error::{Error, Result};
+    use crate::id_types::{Country, IdType};
+    use crate::models::SubmissionResponse;
+    use crate::result_code::ResultCode;
+    use crate::transport::testing::FakeTransport;
+    use chrono::NaiveDate;
+    use std::future::Future;
+    use std::sync::Arc;
+
+    // Response bodies follow the shapes in Smile ID's API reference, with
+    // names, numbers, IDs and signatures replaced.
+    const SUBMITTED: &str = include_str!("../../tests/fixtures/basic_kyc.json");
+    const ENHANCED_KYC: &str = include_str!("../../tests/fixtures/enhanced_kyc.json");
+    const BIOMETRIC_KYC: &str = include_str!("../../tests/fixtures/biometric_kyc.json");
+    const DOCUMENT_VERIFICATION: &str = include_str!("../../tests/fixtures/document_verification.json");
+    const SMARTSELFIE_AUTH: &str = include_str!("../../tests/fixtures/smartselfie_auth.json");
+    const BUSINESS_VERIFICATION: &str = include_str!("../../tests/fixtures/business_verification.json");
+    const UNAUTHORIZED: &str = include_str!("../../tests/fixtures/unauthorized.json");
+    const MISSING_PARAMETER: &str = include_str!("../../tests/fixtures/missing_parameter.json");
+    const JOB_STATUS: &str = include_str!("../../tests/fixtures/job_status.json");
+
+    fn replaying(status: u16, body: &'static str) -> (ApiClient, Arc<FakeTransport>) {
+        let transport = FakeTransport::replying(status, body);
+        let client = ApiClient::with_transport(Config::new("key", "partner"), transport.clone());
+        (client, transport)
+    }
+
+    fn born() -> NaiveDate {
+        NaiveDate::from_ymd_opt(1990, 1, 1).unwrap()
+    }
+
+    /// Runs `submit` against the endpoint's success body and both error
+    /// bodies, checking the URL it hit and returning the parsed success.
+    fn check_endpoint<F, Fut, T: std::fmt::Debug>(path: &str, success: &'static str, submit: F) -> T
+    where
+        F: Fn(ApiClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let url = format!("https://testapi.smileidentity.com/v1/{}", path);
+
+        for (status, body, expected) in [
+            (401, UNAUTHORIZED, "You are not authorized to do that. (code 2205)"),
+            (400, MISSING_PARAMETER, "Missing parameter: id_number (code 2213)"),
+        ] {
+            let (client, _) = replaying(status, body);
+            match tokio_test::block_on(submit(client)).unwrap_err() {
+                Error::Api { message, .. } => assert_eq!(message, expected, "{}", path),
+                other => panic!("{}: expected an API error, got {:?}", path, other),
+            }
+        }
+
+        let (client, recorded) = replaying(200, success);
+        let response = tokio_test::block_on(submit(client)).unwrap();
+        assert_eq!(recorded.urls(), [url.as_str()]);
+        response
+    }
+
+    /// Checks an acknowledgement for a job whose result arrives by callback.
+    fn assert_accepted(response: &SubmissionResponse) {
+        assert_eq!(response.smile_job_id.as_deref(), Some("0000000321"));
+        assert_eq!(response.job_id(), None);
+        assert_eq!(response.fields["success"], true);
+    }
+
+    #[test]
+    fn basic_kyc_responses() {
+        let response = check_endpoint("basic_kyc", SUBMITTED, |client| async move {
+            BasicKyc::new(client).verify("NIN", "00000000000", "NG", None, None, None).await
+        });
+        assert_accepted(&response);
+    }
+
+    #[test]
+    fn enhanced_kyc_responses() {
+        let response = check_endpoint("enhanced_kyc", ENHANCED_KYC, |client| async move {
+            EnhancedKyc::new(client)
+                .verify("NIN", "00000000000", "NG", "Jane", "Doe", born())
+                .await
+        });
+
+        assert_eq!(response.submission.job_id(), Some("job-1"));
+        assert_eq!(response.submission.smile_job_id.as_deref(), Some("0000000321"));
+        assert_eq!(response.submission.result_code, Some(ResultCode::IdValidated));
+        assert_eq!(response.submission.actions.verify_id_number, Some(ActionResult::Verified));
+        assert_eq!(response.full_name.as_deref(), Some("DOE JANE"));
+        assert_eq!(response.dob, Some(ReturnedDateOfBirth::Parsed(DateOfBirth::new(1990, 1, 1).unwrap())));
+        assert_eq!(response.gender.as_deref(), Some("Female"));
+        assert_eq!(response.id_number.as_deref(), Some("00000000000"));
+        assert_eq!(response.submission.fields["Source"], "NIN Lookup");
+
+        let (client, _) = replaying(200, r#"{"SmileJobID": "0000000321", "FullName": "Jane Doe"}"#);
+        let response = tokio_test::block_on(
+            EnhancedKyc::new(client).verify("NIN", "00000000000", "NG", "Jane", "Doe", born()),
+        )
+        .unwrap();
+        assert_eq!(response.submission.job_id(), None);
+        assert_eq!(response.full_name.as_deref(), Some("Jane Doe"));
+    }
+
+    #[test]
+    fn biometric_kyc_responses() {
+        let response = check_endpoint("biometric_kyc", BIOMETRIC_KYC, |client| async move {
+            BiometricKyc::new(client)
+                .verify("NIN", "00000000000", "NG", "Jane", "Doe", born(), "c2VsZmll")
+                .await
+        });
+        assert_accepted(&response);
+    }
+
+    #[test]
+    fn document_verification_responses() {
+        let response = check_endpoint("document_verification", DOCUMENT_VERIFICATION, |client| async move {
+            DocumentVerification::new(client)
+                .verify("PASSPORT", "NG", vec!["ZnJvbnQ=".to_string()])
+                .await
+        });
+        assert_accepted(&response);
+    }
+
+    #[test]
+    fn smartselfie_auth_responses() {
+        let response = check_endpoint("smartselfie_auth", SMARTSELFIE_AUTH, |client| async move {
+            SmartSelfieAuth::new(client).authenticate("user-1", "job-1", "c2VsZmll").await
+        });
+
+        assert_eq!(response.job_id(), Some("job-1"));
+        assert_eq!(response.result_code.as_ref().map(ResultCode::as_str), Some("0820"));
+        assert_eq!(response.actions.liveness_check, Some(ActionResult::Passed));
+        assert_eq!(response.fields["ConfidenceValue"], "99.000000");
+    }
+
+    #[test]
+    fn business_verification_responses() {
+        let response = check_endpoint("business_verification", BUSINESS_VERIFICATION, |client| async move {
+            BusinessVerification::new(client).verify("Acme Ltd", "RC-123", "NG").await
+        });
+
+        assert_eq!(response.job_id(), Some("job-1"));
+        assert_eq!(response.result_code, Some(ResultCode::IdValidated));
+        assert_eq!(response.actions.get("Verify_Business"), Some(&ActionResult::Verified));
+        assert_eq!(response.fields["company_information"]["legal_name"], "ACME LIMITED");
+    }
+
+    #[test]
+    fn invalid_ids_are_rejected_before_sending() {
+        let (client, recorded) = replaying(200, SUBMITTED);
+
+        let unsupported = tokio_test::block_on(
+            BasicKyc::new(client.clone()).verify("KRA_PIN", "A123456789B", "NG", None, None, None),
+        );
+        let malformed = tokio_test::block_on(
+            EnhancedKyc::new(client).verify(IdType::Nin, "1234", Country::Nigeria, "Jane", "Doe", born()),
+        );
+
+        assert!(matches!(unsupported, Err(Error::InvalidParameter(_))));
+        assert!(matches!(malformed, Err(Error::InvalidParameter(_))));
+        assert!(recorded.urls().is_empty());
+    }
+
+    #[test]
+    fn id_numbers_are_sent_as_validated() {
+        let (client, recorded) = replaying(200, SUBMITTED);
+
+        tokio_test::block_on(BasicKyc::new(client.clone()).verify("NIN", " 12345678901 ", "NG", None, None, None))
+            .unwrap();
+        tokio_test::block_on(EnhancedKyc::new(client.clone()).verify("NIN", "12345678901\n", "NG", "Jane", "Doe", born()))
+            .unwrap();
+        tokio_test::block_on(
+            BiometricKyc::new(client).verify("NIN", "\t12345678901", "NG", "Jane", "Doe", born(), "c2VsZmll"),
+        )
+        .unwrap();
+
+        for request in recorded.requests.lock().unwrap().iter() {
+            let sent: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+            assert_eq!(sent["id_number"], "12345678901", "{}", request.url);
+        }
+    }
+
+    #[test]
+    fn dates_of_birth_are_sent_as_full_dates() {
+        let (client, recorded) = replaying(200, SUBMITTED);
+
+        let far_future = NaiveDate::from_ymd_opt(12345, 1, 1).unwrap();
+        let rejected = tokio_test::block_on(
+            BasicKyc::new(client.clone()).verify("NIN", "00000000000", "NG", None, None, Some(far_future)),
+        );
+        assert!(matches!(rejected, Err(Error::InvalidParameter(_))));
+        assert!(recorded.urls().is_empty());
+
+        tokio_test::block_on(BasicKyc::new(client).verify("NIN", "00000000000", "NG", None, None, Some(born())))
+            .unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&recorded.requests.lock().unwrap()[0].body).unwrap();
+        assert_eq!(sent["dob"], "1990-01-01");
+    }
+
+    #[test]
+    fn job_status_responses() {
+        let (client, recorded) = replaying(200, JOB_STATUS);
+        let status = tokio_test::block_on(client.get_job_status("user-1", "job-1", Some(true), Some(true))).unwrap();
+        assert!(status.job_complete && status.job_success);
+        assert_eq!(status.code, Some(ResultCode::from("2302")));
+        assert_eq!(status.result_code(), Some(&ResultCode::IdValidated));
+        assert_eq!(status.actions().unwrap().verify_id_number, Some(ActionResult::Verified));
+        let result = status.result.as_ref().unwrap();
+        assert_eq!(result.partner_params.as_ref().unwrap().job_id, "job-1");
+        assert_eq!(result.fields["ConfidenceValue"], "100");
+        assert_eq!(status.history.as_ref().map(Vec::len), Some(1));
+        assert!(status.image_links.as_ref().unwrap().contains_key("selfie_image"));
+        assert_eq!(status.timestamp.as_deref(), Some("2024-05-14T09:21:37.512Z"));
+        assert_eq!(
+            recorded.urls(),
+            ["https://testapi.smileidentity.com/v1/job_status"]
+        );
+
+        let (client, _) = replaying(401, UNAUTHORIZED);
+        let err = tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap_err();
+        assert!(matches!(err, Error::Api { status_code: 401, .. }));
+    }
+}
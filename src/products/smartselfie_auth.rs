@@ -1,8 +1,8 @@
-use serde::{Deserialize, Serialize};
-
 use crate::api::ApiClient;
+use crate::core::Call;
 use crate::error::Result;
-use crate::models::{ApiResponse, SmartSelfieAuthRequest};
+use crate::models::{SmartSelfieAuthRequest, SubmissionResponse};
+use crate::options::RequestOptions;
 
 #[derive(Debug, Clone)]
 pub struct SmartSelfieAuth {
@@ -13,64 +13,90 @@ impl SmartSelfieAuth {
     pub fn new(client: ApiClient) -> Self {
         Self { client }
     }
-    
+
     pub async fn authenticate(
         &self,
         user_id: impl Into<String>,
         job_id: impl Into<String>,
         selfie_image: impl Into<String>,
-    ) -> Result<String> {
-        let request = SmartSelfieAuthRequest {
+    ) -> Result<SubmissionResponse> {
+        self.authenticate_with_options(
+            user_id,
+            job_id,
+            selfie_image,
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    pub async fn authenticate_with_options(
+        &self,
+        user_id: impl Into<String>,
+        job_id: impl Into<String>,
+        selfie_image: impl Into<String>,
+        options: &RequestOptions,
+    ) -> Result<SubmissionResponse> {
+        let call = call(user_id, job_id, selfie_image);
+        self.client.execute(call, options).await
+    }
+}
+
+fn call(
+    user_id: impl Into<String>,
+    job_id: impl Into<String>,
+    selfie_image: impl Into<String>,
+) -> Call<SmartSelfieAuthRequest> {
+    Call {
+        path: "smartselfie_auth",
+        idempotent: false,
+        payload: SmartSelfieAuthRequest {
             user_id: user_id.into(),
             job_id: job_id.into(),
             selfie_image: selfie_image.into(),
             partner_params: None,
-        };
-        
-        let url = format!("{}/smartselfie_auth", self.client.base_url());
-        let response: ApiResponse<AuthResponse> = self.client.post(&url, &request).await?;
-        
-        Ok(response.data.job_id)
+        },
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct AuthResponse {
-    pub job_id: String,
-}
-
 #[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
     use crate::api::blocking::ApiClient;
-    
+
+    /// Blocking counterpart of [`SmartSelfieAuth`](super::SmartSelfieAuth).
     #[derive(Debug, Clone)]
     pub struct SmartSelfieAuth {
         client: ApiClient,
     }
-    
+
     impl SmartSelfieAuth {
         pub fn new(client: ApiClient) -> Self {
             Self { client }
         }
-        
+
         pub fn authenticate(
             &self,
             user_id: impl Into<String>,
             job_id: impl Into<String>,
             selfie_image: impl Into<String>,
-        ) -> Result<String> {
-            let request = SmartSelfieAuthRequest {
-                user_id: user_id.into(),
-                job_id: job_id.into(),
-                selfie_image: selfie_image.into(),
-                partner_params: None,
-            };
-            
-            let url = format!("{}/smartselfie_auth", self.client.base_url());
-            let response: ApiResponse<AuthResponse> = self.client.post(&url, &request)?;
-            
-            Ok(response.data.job_id)
+        ) -> Result<SubmissionResponse> {
+            self.authenticate_with_options(
+                user_id,
+                job_id,
+                selfie_image,
+                &RequestOptions::default(),
+            )
+        }
+
+        pub fn authenticate_with_options(
+            &self,
+            user_id: impl Into<String>,
+            job_id: impl Into<String>,
+            selfie_image: impl Into<String>,
+            options: &RequestOptions,
+        ) -> Result<SubmissionResponse> {
+            let call = call(user_id, job_id, selfie_image);
+            self.client.execute(call, options)
         }
     }
 }
@@ -0,0 +1,151 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::utils;
+
+/// Token-bucket settings for client-side rate limiting.
+///
+/// The bucket starts full with `capacity` tokens and refills at
+/// `refill_per_second`. Every request attempt consumes one token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    capacity: u32,
+    refill_per_second: f64,
+}
+
+impl RateLimit {
+    /// Fails with `Error::Config` unless `capacity` is at least one and
+    /// `refill_per_second` is a finite, positive rate.
+    pub fn new(capacity: u32, refill_per_second: f64) -> Result<Self> {
+        if capacity == 0 {
+            return Err(Error::Config("Rate limit capacity must be at least one".to_string()));
+        }
+        if !refill_per_second.is_finite() || refill_per_second <= 0.0 {
+            return Err(Error::Config(format!(
+                "Rate limit refill must be a positive number of requests per second, got {}",
+                refill_per_second
+            )));
+        }
+
+        Ok(Self {
+            capacity,
+            refill_per_second,
+        })
+    }
+
+    /// Allows `requests` per second with bursts of the same size.
+    pub fn per_second(requests: u32) -> Result<Self> {
+        Self::new(requests, requests as f64)
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn refill_per_second(&self) -> f64 {
+        self.refill_per_second
+    }
+}
+
+/// A token bucket shared by every clone of the client it belongs to.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        let bucket = Bucket {
+            tokens: limit.capacity as f64,
+            refilled_at: Instant::now(),
+        };
+
+        Self {
+            limit,
+            bucket: Arc::new(Mutex::new(bucket)),
+        }
+    }
+
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    /// The number of tokens currently available.
+    pub fn available(&self) -> f64 {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        self.refill(&mut bucket);
+        bucket.tokens
+    }
+
+    /// Takes a token if one is available, otherwise returns how long to wait
+    /// before trying again.
+    pub fn try_acquire(&self) -> std::result::Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        self.refill(&mut bucket);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let wait = (1.0 - bucket.tokens) / self.limit.refill_per_second;
+        Err(Duration::try_from_secs_f64(wait).unwrap_or(Duration::MAX))
+    }
+
+    /// Waits until a token is available and takes it.
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            utils::sleep(wait).await;
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.refilled_at = now;
+
+        bucket.tokens =
+            (bucket.tokens + elapsed * self.limit.refill_per_second).min(self.limit.capacity as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_is_shared_between_clones() {
+        let limiter = RateLimiter::new(RateLimit::new(2, 0.001).unwrap());
+        let clone = limiter.clone();
+
+        assert!(limiter.try_acquire().is_ok());
+        assert!(clone.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+        assert!(clone.available() < 1.0);
+    }
+
+    #[test]
+    fn empty_bucket_reports_time_until_next_token() {
+        let limiter = RateLimiter::new(RateLimit::new(1, 10.0).unwrap());
+
+        assert!(limiter.try_acquire().is_ok());
+        let wait = limiter.try_acquire().unwrap_err();
+
+        assert!(wait <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn limits_that_would_never_refill_are_rejected() {
+        for (capacity, refill) in [(0, 1.0), (1, 0.0), (1, -1.0), (1, f64::NAN), (1, f64::INFINITY)] {
+            assert!(matches!(RateLimit::new(capacity, refill), Err(Error::Config(_))), "{} {}", capacity, refill);
+        }
+    }
+}
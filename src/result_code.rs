@@ -0,0 +1,301 @@
+use serde::de::{self, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// How a job result should be acted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResultOutcome {
+    /// The check passed.
+    Approved,
+    /// The check failed.
+    Rejected,
+    /// The check passed provisionally and is waiting for a manual review.
+    Provisional,
+    /// The job could not be completed, e.g. because the ID authority was unavailable.
+    SystemError,
+    /// The request was refused before a job ran, e.g. a parameter was missing
+    /// or the job already exists. Fix the request before sending it again.
+    RequestError,
+    /// The partner is not authorized to make the request. Check the API key,
+    /// signature and account permissions.
+    AuthorizationError,
+}
+
+macro_rules! result_codes {
+    ($($(#[doc = $doc:literal])* $variant:ident = $code:literal => $outcome:ident,)*) => {
+        /// A result code from Smile ID's published catalogue.
+        ///
+        /// Codes the catalogue does not know yet are kept as `Unknown`, so new
+        /// codes never break deserialization.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum ResultCode {
+            $($(#[doc = $doc])* $variant,)*
+            Unknown(String),
+        }
+
+        impl ResultCode {
+            /// The code as Smile ID sends it, e.g. `"1012"`.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(ResultCode::$variant => $code,)*
+                    ResultCode::Unknown(code) => code,
+                }
+            }
+
+            /// How the result should be acted on, or `None` for unknown codes.
+            pub fn outcome(&self) -> Option<ResultOutcome> {
+                match self {
+                    $(ResultCode::$variant => Some(ResultOutcome::$outcome),)*
+                    ResultCode::Unknown(_) => None,
+                }
+            }
+        }
+
+        impl From<&str> for ResultCode {
+            fn from(code: &str) -> Self {
+                match code.trim() {
+                    $($code => ResultCode::$variant,)*
+                    code => ResultCode::Unknown(code.to_string()),
+                }
+            }
+        }
+    };
+}
+
+result_codes! {
+    // Document Verification and SmartSelfie registration
+    /// `0810`: the document was verified or the user enrolled.
+    Verified = "0810" => Approved,
+    /// `0811`: provisionally approved, pending a manual review.
+    ProvisionallyVerified = "0811" => Provisional,
+    /// `0812`: rejected by machine judgement.
+    RejectedByMachine = "0812" => Rejected,
+    /// `0813`: rejected on manual review.
+    RejectedOnReview = "0813" => Rejected,
+
+    // SmartSelfie Authentication
+    /// `0820`: the selfie matches the enrolled user.
+    Authenticated = "0820" => Approved,
+    /// `0821`: provisionally authenticated, pending a manual review.
+    ProvisionallyAuthenticated = "0821" => Provisional,
+    /// `0822`: the selfie does not match the enrolled user.
+    AuthenticationFailed = "0822" => Rejected,
+
+    // SmartSelfie image checks
+    /// `0911`: no face was found in the selfie.
+    NoFaceFound = "0911" => Rejected,
+    /// `0921`: no face was found in the ID photo.
+    IdFaceNotFound = "0921" => Rejected,
+    /// `0922`: the selfie is too poor in quality to compare.
+    SelfieQualityTooPoor = "0922" => Rejected,
+
+    // Basic KYC, Enhanced KYC and Business Verification
+    /// `1012`: the ID number was found and validated.
+    IdValidated = "1012" => Approved,
+    /// `1013`: the ID authority has no record of the ID number.
+    IdNotFound = "1013" => Rejected,
+    /// `1014`: the ID type is not supported.
+    UnsupportedIdType = "1014" => SystemError,
+    /// `1015`: the ID authority could not be reached.
+    IdAuthorityUnavailable = "1015" => SystemError,
+    /// `1016`: the product is not activated for the partner account.
+    ProductNotActivated = "1016" => SystemError,
+    /// `1020`: the submitted details exactly match the ID authority's record.
+    ExactMatch = "1020" => Approved,
+    /// `1021`: the submitted details partially match the ID authority's record.
+    PartialMatch = "1021" => Provisional,
+    /// `1022`: the submitted details do not match the ID authority's record.
+    NoMatch = "1022" => Rejected,
+
+    // Biometric KYC
+    /// `1210`: the selfie matches the ID authority's photo and the user was enrolled.
+    Enrolled = "1210" => Approved,
+    /// `1211`: provisionally enrolled, pending a manual review.
+    ProvisionallyEnrolled = "1211" => Provisional,
+    /// `1212`: enrollment failed because the selfie does not match.
+    EnrollmentFailed = "1212" => Rejected,
+    /// `1213`: enrollment failed because the ID number was not found.
+    EnrollmentIdNotFound = "1213" => Rejected,
+
+    // Authorization errors
+    /// `2204`: the request was not authorized.
+    Unauthorized = "2204" => AuthorizationError,
+    /// `2205`: the partner is not authorized for this action.
+    Forbidden = "2205" => AuthorizationError,
+
+    // Request errors
+    /// `2213`: a required parameter was missing.
+    MissingParameter = "2213" => RequestError,
+    /// `2215`: a job with this job ID already exists.
+    JobAlreadyExists = "2215" => RequestError,
+}
+
+impl ResultCode {
+    pub fn is_approved(&self) -> bool {
+        self.outcome() == Some(ResultOutcome::Approved)
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        self.outcome() == Some(ResultOutcome::Rejected)
+    }
+
+    /// Whether the result is provisional and waiting for a manual review.
+    pub fn needs_review(&self) -> bool {
+        self.outcome() == Some(ResultOutcome::Provisional)
+    }
+
+    pub fn is_system_error(&self) -> bool {
+        self.outcome() == Some(ResultOutcome::SystemError)
+    }
+
+    /// Whether the request itself was at fault, so sending it again unchanged will fail again.
+    pub fn is_request_error(&self) -> bool {
+        self.outcome() == Some(ResultOutcome::RequestError)
+    }
+
+    pub fn is_authorization_error(&self) -> bool {
+        self.outcome() == Some(ResultOutcome::AuthorizationError)
+    }
+}
+
+impl From<String> for ResultCode {
+    fn from(code: String) -> Self {
+        match ResultCode::from(code.as_str()) {
+            ResultCode::Unknown(_) => ResultCode::Unknown(code),
+            known => known,
+        }
+    }
+}
+
+impl fmt::Display for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ResultCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Accepts codes sent as strings or as numbers, which lose their leading
+/// zeros and are padded back to four digits.
+impl<'de> Deserialize<'de> for ResultCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(ResultCodeVisitor)
+    }
+}
+
+struct ResultCodeVisitor;
+
+impl Visitor<'_> for ResultCodeVisitor {
+    type Value = ResultCode;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a result code as a string or an integer")
+    }
+
+    fn visit_str<E: de::Error>(self, code: &str) -> std::result::Result<ResultCode, E> {
+        Ok(ResultCode::from(code))
+    }
+
+    fn visit_string<E: de::Error>(self, code: String) -> std::result::Result<ResultCode, E> {
+        Ok(ResultCode::from(code))
+    }
+
+    fn visit_u64<E: de::Error>(self, code: u64) -> std::result::Result<ResultCode, E> {
+        Ok(ResultCode::from(format!("{:04}", code)))
+    }
+
+    fn visit_i64<E: de::Error>(self, code: i64) -> std::result::Result<ResultCode, E> {
+        u64::try_from(code)
+            .map_err(|_| E::invalid_value(Unexpected::Signed(code), &self))
+            .and_then(|code| self.visit_u64(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcomes(codes: &[&str]) -> Vec<Option<ResultOutcome>> {
+        codes.iter().map(|&code| ResultCode::from(code).outcome()).collect()
+    }
+
+    #[test]
+    fn known_codes_are_classified() {
+        assert_eq!(ResultCode::from("1012"), ResultCode::IdValidated);
+        assert_eq!(ResultCode::from("0810").as_str(), "0810");
+    }
+
+    #[test]
+    fn approved_codes() {
+        let codes = ["0810", "0820", "1012", "1020", "1210"];
+
+        assert!(codes.iter().all(|&code| ResultCode::from(code).is_approved()), "{:?}", outcomes(&codes));
+    }
+
+    #[test]
+    fn provisional_codes() {
+        let codes = ["0811", "0821", "1021", "1211"];
+
+        assert!(codes.iter().all(|&code| ResultCode::from(code).needs_review()), "{:?}", outcomes(&codes));
+    }
+
+    #[test]
+    fn rejected_codes() {
+        let codes = ["0812", "0813", "0822", "0911", "0921", "0922", "1013", "1022", "1212", "1213"];
+
+        assert!(codes.iter().all(|&code| ResultCode::from(code).is_rejected()), "{:?}", outcomes(&codes));
+    }
+
+    #[test]
+    fn system_error_codes() {
+        let codes = ["1014", "1015", "1016"];
+
+        assert!(codes.iter().all(|&code| ResultCode::from(code).is_system_error()), "{:?}", outcomes(&codes));
+    }
+
+    #[test]
+    fn request_error_codes() {
+        let codes = ["2213", "2215"];
+
+        assert!(codes.iter().all(|&code| ResultCode::from(code).is_request_error()), "{:?}", outcomes(&codes));
+        assert!(!ResultCode::MissingParameter.is_system_error());
+    }
+
+    #[test]
+    fn authorization_error_codes() {
+        let codes = ["2204", "2205"];
+
+        assert!(codes.iter().all(|&code| ResultCode::from(code).is_authorization_error()), "{:?}", outcomes(&codes));
+        assert!(!ResultCode::Forbidden.is_system_error());
+    }
+
+    #[test]
+    fn unknown_codes_round_trip() {
+        let code: ResultCode = serde_json::from_str(r#""9999""#).unwrap();
+
+        assert_eq!(code, ResultCode::Unknown("9999".to_string()));
+        assert_eq!(code.outcome(), None);
+        assert_eq!(serde_json::to_string(&code).unwrap(), r#""9999""#);
+    }
+
+    #[test]
+    fn numeric_codes_are_zero_padded() {
+        let codes: Vec<ResultCode> = serde_json::from_str(r#"[810, 1012, "0811", 12345]"#).unwrap();
+
+        assert_eq!(
+            codes,
+            [
+                ResultCode::from("0810"),
+                ResultCode::IdValidated,
+                ResultCode::from("0811"),
+                ResultCode::Unknown("12345".to_string()),
+            ]
+        );
+        assert!(serde_json::from_str::<ResultCode>("-1").is_err());
+        assert!(serde_json::from_str::<ResultCode>("10.5").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use std::time::Duration;
+
+use crate::error::Error;
+
+/// Controls how [`ApiClient::post`](crate::ApiClient::post) retries failed requests.
+///
+/// Failures are retried with exponential backoff, and every retry is signed
+/// again with a fresh timestamp. Connection failures and `429` responses, which
+/// Smile ID never acted on, are always retried.
+///
+/// Timeouts, dropped connections, transport errors and the other listed status
+/// codes may come after Smile ID accepted the request. Those are only retried
+/// for lookups such as `job_status` and `/services`, unless
+/// `retry_submissions` is set: resending a product submission can then create
+/// a second job, and a second charge, for the same request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Randomise each delay between zero and the computed backoff.
+    pub jitter: bool,
+    pub retryable_status_codes: Vec<u16>,
+    /// Also retry submissions that may already have been processed.
+    pub retry_submissions: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            retryable_status_codes: vec![429, 500, 502, 503, 504],
+            retry_submissions: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request exactly once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_retryable_status_codes(mut self, status_codes: impl Into<Vec<u16>>) -> Self {
+        self.retryable_status_codes = status_codes.into();
+        self
+    }
+
+    /// Opts into resending submissions after failures that may have reached
+    /// Smile ID, at the risk of submitting the same job twice.
+    pub fn with_submission_retries(mut self, retry_submissions: bool) -> Self {
+        self.retry_submissions = retry_submissions;
+        self
+    }
+
+    /// Returns whether a request that failed with `error` on attempt number
+    /// `attempt` (starting at 1) should be sent again. `idempotent` requests,
+    /// which are harmless to repeat, are retried after any retryable failure.
+    pub fn should_retry(&self, error: &Error, attempt: u32, idempotent: bool) -> bool {
+        attempt < self.max_attempts && self.is_retryable(error, idempotent || self.retry_submissions)
+    }
+
+    /// The delay to wait after attempt number `attempt` (starting at 1) failed.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+
+        if self.jitter {
+            backoff.mul_f64(fastrand::f64())
+        } else {
+            backoff
+        }
+    }
+
+    /// Whether `error` may be retried; unless `repeatable`, only failures that
+    /// cannot have been processed are.
+    fn is_retryable(&self, error: &Error, repeatable: bool) -> bool {
+        match error {
+            Error::Http(e) if e.is_connect() => true,
+            Error::Http(e) => repeatable && (e.is_timeout() || e.is_request() || e.is_body()),
+            Error::Transport(_) => repeatable,
+            Error::Api { status_code, .. } => {
+                self.retryable_status_codes.contains(status_code) && (repeatable || *status_code == 429)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Wraps the last error once more than one attempt has been made. Cancelling
+/// the call or finding the circuit open stops retrying rather than exhausting
+/// it, so those are returned as they are.
+pub(crate) fn exhausted(error: Error, attempts: u32) -> Error {
+    match error {
+        Error::Cancelled | Error::CircuitOpen { .. } => error,
+        error if attempts > 1 => Error::RetriesExhausted {
+            attempts,
+            source: Box::new(error),
+        },
+        error => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Environment;
+
+    #[test]
+    fn backoff_doubles_up_to_max_delay() {
+        let policy = RetryPolicy::default()
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500))
+            .with_jitter(false);
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(500));
+        assert_eq!(policy.delay(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn only_listed_status_codes_are_retried() {
+        let policy = RetryPolicy::default().with_max_attempts(3);
+        let unavailable = Error::Api {
+            status_code: 503,
+            message: String::new(),
+            environment: Environment::Sandbox,
+        };
+        let bad_request = Error::Api {
+            status_code: 400,
+            message: String::new(),
+            environment: Environment::Sandbox,
+        };
+
+        assert!(policy.should_retry(&unavailable, 1, true));
+        assert!(!policy.should_retry(&unavailable, 3, true));
+        assert!(!policy.should_retry(&bad_request, 1, true));
+    }
+
+    #[test]
+    fn submissions_are_only_retried_when_they_cannot_have_been_processed() {
+        let policy = RetryPolicy::default();
+        let status = |status_code| Error::Api {
+            status_code,
+            message: String::new(),
+            environment: Environment::Sandbox,
+        };
+        let dropped = Error::Transport("connection reset".to_string());
+
+        assert!(policy.should_retry(&status(429), 1, false));
+        assert!(!policy.should_retry(&status(503), 1, false));
+        assert!(!policy.should_retry(&dropped, 1, false));
+        assert!(policy.should_retry(&dropped, 1, true));
+
+        let opted_in = policy.with_submission_retries(true);
+        assert!(opted_in.should_retry(&status(503), 1, false));
+        assert!(opted_in.should_retry(&dropped, 1, false));
+    }
+
+    #[test]
+    fn cancelled_and_circuit_open_calls_are_not_reported_as_exhausted() {
+        let circuit_open = Error::CircuitOpen {
+            retry_after: Duration::from_secs(1),
+        };
+
+        assert!(matches!(exhausted(Error::Cancelled, 2), Error::Cancelled));
+        assert!(matches!(exhausted(circuit_open, 3), Error::CircuitOpen { .. }));
+        assert!(matches!(exhausted(Error::Other(String::new()), 2), Error::RetriesExhausted { attempts: 2, .. }));
+    }
+}
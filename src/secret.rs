@@ -0,0 +1,78 @@
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// A Smile ID API key.
+///
+/// The key is redacted in `Debug` and `Display` output. Clones share one
+/// buffer, which is wiped from memory when the last clone is dropped. Only
+/// [`Auth`](crate::Auth) reads it back, to sign requests.
+#[derive(Clone)]
+pub struct ApiKey(Arc<Zeroizing<String>>);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(Arc::new(Zeroizing::new(key.into())))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        Self::new(key)
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey([REDACTED])")
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_and_display_never_print_the_key() {
+        let key = ApiKey::new("super-secret");
+
+        assert_eq!(format!("{:?}", key), "ApiKey([REDACTED])");
+        assert_eq!(key.to_string(), "[REDACTED]");
+        assert_eq!(key.expose(), "super-secret");
+    }
+
+    #[test]
+    fn clones_share_one_buffer() {
+        let key = ApiKey::new("super-secret");
+        let clone = key.clone();
+
+        assert!(std::ptr::eq(key.expose(), clone.expose()));
+    }
+}
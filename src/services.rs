@@ -0,0 +1,272 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::{poll_fn, Future};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// The markets, ID types and products Smile ID currently supports, as returned
+/// by the `/services` endpoint.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Services {
+    /// The fields each ID type requires, keyed by country code and then ID type.
+    #[serde(default)]
+    pub id_types: HashMap<String, HashMap<String, Vec<String>>>,
+    /// Where each product is available, keyed by product (e.g. `biometric_kyc`)
+    /// and then country code.
+    #[serde(default)]
+    pub hosted_web: HashMap<String, HashMap<String, CountryServices>>,
+}
+
+/// A product's coverage in one country.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CountryServices {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub id_types: HashMap<String, IdTypeService>,
+}
+
+/// How a product handles one ID type.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IdTypeService {
+    pub label: Option<String>,
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+    pub id_number_regex: Option<String>,
+    pub test_data: Option<String>,
+}
+
+impl Services {
+    /// The country codes with at least one supported ID type.
+    pub fn countries(&self) -> impl Iterator<Item = &str> {
+        self.id_types.keys().map(String::as_str)
+    }
+
+    /// The ID types supported in `country`, with the fields each one requires.
+    pub fn id_types(&self, country: &str) -> Option<&HashMap<String, Vec<String>>> {
+        self.id_types.get(country)
+    }
+
+    /// The fields a request for `id_type` in `country` must include.
+    pub fn required_fields(&self, country: &str, id_type: &str) -> Option<&[String]> {
+        self.id_types(country)?.get(id_type).map(Vec::as_slice)
+    }
+
+    /// The products available in `country`.
+    pub fn products<'a>(&'a self, country: &'a str) -> impl Iterator<Item = &'a str> {
+        self.hosted_web
+            .iter()
+            .filter(move |(_, countries)| countries.contains_key(country))
+            .map(|(product, _)| product.as_str())
+    }
+
+    /// How `product` handles `id_type` in `country`, if it supports it there.
+    pub fn product_id_type(&self, product: &str, country: &str, id_type: &str) -> Option<&IdTypeService> {
+        self.hosted_web.get(product)?.get(country)?.id_types.get(id_type)
+    }
+}
+
+/// The last `/services` response, shared by a client and its clones.
+///
+/// Only one caller refreshes an expired entry at a time; the others wait for
+/// its result instead of sending their own request.
+#[derive(Debug, Clone)]
+pub(crate) struct ServicesCache {
+    ttl: Duration,
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    state: Mutex<State>,
+    /// Notified with `state` locked when a refresh ends, for blocking waits.
+    refreshed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    cached: Option<(Instant, Services)>,
+    refreshing: bool,
+    /// Tasks waiting for the refresh in progress.
+    waiters: Vec<Waker>,
+}
+
+/// What a caller should do after looking in the cache.
+pub(crate) enum Lookup {
+    Cached(Services),
+    /// Another caller is refreshing; wait for it and look again.
+    Wait,
+    /// This caller fetches the services and stores them with the guard.
+    Refresh(Refresh),
+}
+
+/// Held by the one caller refreshing the cache. Dropping it without
+/// [`finish`](Refresh::finish), e.g. because the fetch failed, lets a waiting
+/// caller try instead.
+pub(crate) struct Refresh {
+    cache: ServicesCache,
+}
+
+impl ServicesCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            inner: Arc::default(),
+        }
+    }
+
+    pub fn lookup(&self) -> Lookup {
+        let mut state = self.state();
+        if let Some(services) = self.fresh(&state) {
+            return Lookup::Cached(services);
+        }
+        if state.refreshing {
+            return Lookup::Wait;
+        }
+
+        state.refreshing = true;
+        Lookup::Refresh(Refresh { cache: self.clone() })
+    }
+
+    /// Resolves once no refresh is in progress.
+    pub fn refreshed(&self) -> impl Future<Output = ()> + '_ {
+        poll_fn(|cx| {
+            let mut state = self.state();
+            if !state.refreshing {
+                return Poll::Ready(());
+            }
+            if !state.waiters.iter().any(|waiter| waiter.will_wake(cx.waker())) {
+                state.waiters.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+    }
+
+    /// Blocks the current thread until no refresh is in progress.
+    #[cfg(feature = "blocking")]
+    pub fn wait_blocking(&self) {
+        let state = self.state();
+        let _state = self
+            .inner
+            .refreshed
+            .wait_while(state, |state| state.refreshing)
+            .unwrap_or_else(|e| e.into_inner());
+    }
+
+    fn fresh(&self, state: &State) -> Option<Services> {
+        match &state.cached {
+            Some((fetched, services)) if fetched.elapsed() < self.ttl => Some(services.clone()),
+            _ => None,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Refresh {
+    pub fn finish(self, services: &Services) {
+        self.cache.state().cached = Some((Instant::now(), services.clone()));
+    }
+}
+
+impl Drop for Refresh {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.cache.state();
+            state.refreshing = false;
+            self.cache.inner.refreshed.notify_all();
+            std::mem::take(&mut state.waiters)
+        };
+        for waiter in waiters {
+            waiter.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVICES: &str = r#"{
+        "id_types": {
+            "NG": {"BVN": ["country", "id_type", "id_number"], "NIN": ["country", "id_type", "id_number"]},
+            "KE": {"NATIONAL_ID": ["country", "id_type", "id_number", "first_name", "last_name"]}
+        },
+        "hosted_web": {
+            "biometric_kyc": {
+                "KE": {
+                    "name": "Kenya",
+                    "id_types": {
+                        "NATIONAL_ID": {
+                            "label": "National ID",
+                            "required_fields": ["country", "id_type", "id_number"],
+                            "id_number_regex": "^[0-9]{1,9}$",
+                            "test_data": "00000000"
+                        }
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn looks_up_id_types_and_products_per_country() {
+        let services: Services = serde_json::from_str(SERVICES).unwrap();
+
+        assert_eq!(
+            services.required_fields("KE", "NATIONAL_ID").unwrap(),
+            ["country", "id_type", "id_number", "first_name", "last_name"]
+        );
+        assert_eq!(services.id_types("NG").unwrap().len(), 2);
+        assert_eq!(services.products("KE").collect::<Vec<_>>(), ["biometric_kyc"]);
+        assert_eq!(services.products("NG").count(), 0);
+        let national_id = services.product_id_type("biometric_kyc", "KE", "NATIONAL_ID").unwrap();
+        assert_eq!(national_id.id_number_regex.as_deref(), Some("^[0-9]{1,9}$"));
+    }
+
+    #[test]
+    fn cache_expires_after_the_ttl() {
+        let fresh = ServicesCache::new(Duration::from_secs(60));
+        let stale = ServicesCache::new(Duration::ZERO);
+
+        for cache in [&fresh, &stale] {
+            match cache.lookup() {
+                Lookup::Refresh(refresh) => refresh.finish(&Services::default()),
+                _ => panic!("an empty cache should be refreshed"),
+            }
+        }
+
+        assert!(matches!(fresh.clone().lookup(), Lookup::Cached(_)));
+        assert!(matches!(stale.lookup(), Lookup::Refresh(_)));
+    }
+
+    #[test]
+    fn one_caller_refreshes_at_a_time() {
+        let cache = ServicesCache::new(Duration::from_secs(60));
+
+        let Lookup::Refresh(refresh) = cache.lookup() else { panic!("expected to refresh") };
+        assert!(matches!(cache.clone().lookup(), Lookup::Wait));
+
+        let waiter = cache.clone();
+        let handle = std::thread::spawn(move || {
+            tokio_test::block_on(waiter.refreshed());
+            matches!(waiter.lookup(), Lookup::Cached(_))
+        });
+        refresh.finish(&Services::default());
+
+        assert!(handle.join().unwrap());
+        assert!(matches!(cache.lookup(), Lookup::Cached(_)));
+    }
+
+    #[test]
+    fn a_failed_refresh_lets_the_next_caller_try() {
+        let cache = ServicesCache::new(Duration::from_secs(60));
+
+        let Lookup::Refresh(refresh) = cache.lookup() else { panic!("expected to refresh") };
+        drop(refresh);
+
+        assert!(matches!(cache.lookup(), Lookup::Refresh(_)));
+    }
+}
@@ -0,0 +1,209 @@
+//! Request spans and failure events, compiled in with the `tracing` feature.
+//!
+//! Only the product, environment, endpoint path, partner ID, job ID, attempt,
+//! HTTP status and latency are recorded. Request and response bodies are never attached,
+//! so ID numbers, names, dates of birth and images stay out of traces.
+
+use std::future::Future;
+use std::time::Instant;
+
+use crate::config::Environment;
+use crate::error::{Error, Result};
+use crate::transport::{HttpRequest, HttpResponse};
+
+/// The span covering one logical API call, including all of its retries.
+pub(crate) struct RequestSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+#[cfg(feature = "tracing")]
+impl RequestSpan {
+    pub(crate) fn new(partner_id: &str, environment: &Environment, url: &str) -> Self {
+        let endpoint = endpoint_path(url);
+        let product = endpoint.rsplit('/').next().unwrap_or_default();
+
+        Self {
+            span: tracing::info_span!(
+                "smile_id.request",
+                product = %product,
+                environment = %environment,
+                endpoint = %endpoint,
+                partner_id = %partner_id,
+                job_id = tracing::field::Empty,
+                attempt = tracing::field::Empty,
+                http.status_code = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            ),
+        }
+    }
+
+    /// Runs `f`, e.g. interceptors, inside the span.
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.span.in_scope(f)
+    }
+
+    /// Runs `future`, e.g. the transport, inside the span.
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        tracing::Instrument::instrument(future, self.span.clone())
+    }
+
+    /// Records the job ID the request was sent with, until a response names one.
+    pub(crate) fn record_request(&self, request: &HttpRequest) {
+        if let Some(job_id) = request_job_id(&request.body) {
+            self.span.record("job_id", job_id.as_str());
+        }
+    }
+
+    /// Records the outcome of a single attempt that started at `started`.
+    pub(crate) fn record_attempt(&self, attempt: u32, started: Instant, outcome: &Result<HttpResponse>) {
+        let latency_ms = started.elapsed().as_millis() as u64;
+        self.span.record("attempt", attempt);
+        self.span.record("latency_ms", latency_ms);
+
+        match outcome {
+            Ok(response) => {
+                self.span.record("http.status_code", response.status);
+                if let Some(job_id) = response_job_id(&response.body) {
+                    self.span.record("job_id", job_id.as_str());
+                }
+                if !(200..300).contains(&response.status) {
+                    tracing::warn!(
+                        parent: &self.span,
+                        attempt,
+                        http.status_code = response.status,
+                        latency_ms,
+                        "Smile ID returned an error status"
+                    );
+                }
+            }
+            Err(e) => tracing::warn!(
+                parent: &self.span,
+                attempt,
+                latency_ms,
+                error = %redacted(e),
+                "Smile ID request failed"
+            ),
+        }
+    }
+
+    /// Records the error the call finally returns, after any retries.
+    pub(crate) fn record_failure(&self, error: &Error) {
+        tracing::error!(parent: &self.span, error = %redacted(error), "Smile ID call failed");
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl RequestSpan {
+    pub(crate) fn new(_partner_id: &str, _environment: &Environment, _url: &str) -> Self {
+        Self {}
+    }
+
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> F {
+        future
+    }
+
+    pub(crate) fn record_request(&self, _request: &HttpRequest) {}
+
+    pub(crate) fn record_attempt(&self, _attempt: u32, _started: Instant, _outcome: &Result<HttpResponse>) {}
+
+    pub(crate) fn record_failure(&self, _error: &Error) {}
+}
+
+#[cfg(feature = "tracing")]
+fn endpoint_path(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_default()
+}
+
+/// The job IDs a body carries, read without touching any other field.
+#[cfg(feature = "tracing")]
+#[derive(serde::Deserialize)]
+struct JobIds {
+    job_id: Option<String>,
+    #[serde(rename = "PartnerParams", alias = "partner_params")]
+    partner_params: Option<PartnerJobId>,
+}
+
+#[cfg(feature = "tracing")]
+#[derive(serde::Deserialize)]
+struct PartnerJobId {
+    job_id: Option<String>,
+}
+
+/// The job ID in a response's `PartnerParams`.
+#[cfg(feature = "tracing")]
+fn response_job_id(body: &str) -> Option<String> {
+    serde_json::from_str::<JobIds>(body).ok()?.partner_params?.job_id
+}
+
+/// The job ID a request payload was sent with, at the top level as for
+/// `job_status` or in its `partner_params`.
+#[cfg(feature = "tracing")]
+fn request_job_id(body: &str) -> Option<String> {
+    let ids = serde_json::from_str::<JobIds>(body).ok()?;
+    ids.job_id.or_else(|| ids.partner_params?.job_id)
+}
+
+/// Describes an error without the response message or JSON error text, both
+/// of which may echo request data.
+#[cfg(feature = "tracing")]
+fn redacted(error: &Error) -> String {
+    match error {
+        Error::Api { status_code, .. } => format!("API error: {}", status_code),
+        Error::Json(e) => format!("JSON error: {:?} at line {} column {}", e.classify(), e.line(), e.column()),
+        Error::RetriesExhausted { attempts, source } => {
+            format!("Request failed after {} attempts: {}", attempts, redacted(source))
+        }
+        e => e.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_path_drops_the_host() {
+        assert_eq!(
+            endpoint_path("https://api.smileidentity.com/v1/basic_kyc"),
+            "/v1/basic_kyc"
+        );
+    }
+
+    #[test]
+    fn job_id_is_read_without_touching_other_fields() {
+        let request = r#"{"partner_params": {"job_id": "job-1", "user_id": "user-1"}, "id_number": "A123"}"#;
+        let response = r#"{"SmileJobID": "0000000321", "PartnerParams": {"job_id": "job-2"}, "FullName": "Jane"}"#;
+
+        assert_eq!(request_job_id(request).as_deref(), Some("job-1"));
+        assert_eq!(request_job_id(r#"{"job_id": "job-3", "user_id": "user-1"}"#).as_deref(), Some("job-3"));
+        assert_eq!(response_job_id(response).as_deref(), Some("job-2"));
+        assert_eq!(response_job_id(r#"{"success": true, "job_id": "job-4"}"#), None);
+        assert_eq!(request_job_id("not json"), None);
+    }
+
+    #[test]
+    fn api_errors_are_redacted() {
+        let error = Error::Api {
+            status_code: 400,
+            message: "Invalid id_number A123".to_string(),
+            environment: Environment::Sandbox,
+        };
+
+        assert_eq!(redacted(&error), "API error: 400");
+    }
+
+    #[test]
+    fn json_errors_do_not_echo_values() {
+        let error = Error::from(serde_json::from_str::<u64>(r#""12345678901""#).unwrap_err());
+
+        assert!(error.to_string().contains("12345678901"));
+        assert_eq!(redacted(&error), "JSON error: Data at line 1 column 13");
+    }
+}
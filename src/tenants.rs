@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::api::ApiClient;
+use crate::callbacks::{self, CallbackHandler, CallbackVerifier, JobResult, VerificationFailure};
+use crate::config::Config;
+use crate::credentials::CredentialProvider;
+use crate::error::{Error, Result};
+use crate::transport::Transport;
+
+/// Clients for several partner accounts that share one transport and connection pool.
+///
+/// Each tenant signs with the credentials from its own [`Config`], and
+/// [`handle_callback`](Self::handle_callback) routes callbacks to the tenant
+/// whose partner ID and key they were signed with.
+///
+/// The transport's client is built once, so a tenant's `Config::timeout` and
+/// `Config::http` are ignored; configure them on the transport passed to
+/// [`new`](Self::new), or set a per-call timeout with `RequestOptions`.
+#[derive(Debug, Clone)]
+pub struct TenantRegistry {
+    transport: Arc<dyn Transport>,
+    tenants: HashMap<String, Tenant>,
+}
+
+#[derive(Debug, Clone)]
+struct Tenant {
+    client: ApiClient,
+    partner_id: String,
+    callbacks: CallbackHandler,
+}
+
+impl TenantRegistry {
+    /// Creates a registry whose clients all send requests through `transport`,
+    /// e.g. a `ReqwestTransport` built once for the whole process.
+    pub fn new(transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            tenants: HashMap::new(),
+        }
+    }
+
+    /// Registers `tenant`, replacing any tenant already registered under that name.
+    ///
+    /// Only the credentials, environment, retry, rate limit and circuit
+    /// breaker settings of `config` apply; timeouts and HTTP options come
+    /// from the shared transport.
+    pub fn with_tenant(mut self, name: impl Into<String>, config: Config) -> Self {
+        let credentials = config.credential_provider();
+        let partner_id = credentials
+            .current()
+            .map(|auth| auth.partner_id().to_string())
+            .unwrap_or_else(|_| config.partner_id.clone());
+
+        let tenant = Tenant {
+            callbacks: CallbackHandler::with_verifier(CallbackVerifier::from_credentials(credentials)),
+            client: ApiClient::with_shared_transport(config, self.transport.clone()),
+            partner_id,
+        };
+        self.tenants.insert(name.into(), tenant);
+        self
+    }
+
+    /// The client for `tenant`.
+    pub fn client(&self, tenant: &str) -> Result<&ApiClient> {
+        self.tenants
+            .get(tenant)
+            .map(|tenant| &tenant.client)
+            .ok_or_else(|| Error::InvalidParameter(format!("Unknown tenant {:?}", tenant)))
+    }
+
+    /// The names of every registered tenant.
+    pub fn tenants(&self) -> impl Iterator<Item = &str> {
+        self.tenants.keys().map(String::as_str)
+    }
+
+    /// Verifies a callback and returns it with the name of the tenant it belongs to.
+    ///
+    /// Callbacks carrying a partner ID, in the body or the `SmileID-Partner-ID`
+    /// header, are only checked against tenants with that partner ID. Otherwise
+    /// the tenant is the one whose key the signature was made with.
+    pub fn handle_callback<I, K, V>(&self, headers: I, body: &[u8]) -> Result<(&str, JobResult)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let (result, signed) = callbacks::parse(headers, body)?;
+        let candidates: Vec<_> = self
+            .tenants
+            .iter()
+            .filter(|(_, tenant)| match &signed.partner_id {
+                Some(partner_id) => *partner_id == tenant.partner_id,
+                None => true,
+            })
+            .collect();
+        if let (Some(partner_id), true) = (&signed.partner_id, candidates.is_empty()) {
+            return Err(Error::InvalidParameter(format!("No tenant for partner ID {:?}", partner_id)));
+        }
+
+        let mut error = Error::SignatureVerification(VerificationFailure::InvalidSignature);
+        for (name, tenant) in candidates {
+            match tenant.callbacks.verify(&signed) {
+                Ok(()) => return Ok((name, result)),
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{format_timestamp, Auth};
+    use crate::transport::testing::FakeTransport;
+    use chrono::Utc;
+
+    const JOB_STATUS_BODY: &str = include_str!("../tests/fixtures/job_status.json");
+
+    fn registry(transport: Arc<FakeTransport>) -> TenantRegistry {
+        TenantRegistry::new(transport)
+            .with_tenant("retail", Config::new("retail-key", "001"))
+            .with_tenant("lending", Config::new("lending-key", "002"))
+    }
+
+    fn callback(auth: &Auth, partner_id: Option<&str>) -> String {
+        let now = Utc::now();
+        let mut body = serde_json::json!({
+            "SmileJobID": "0000000321",
+            "PartnerParams": { "job_id": "job-1", "user_id": "user-1" },
+            "signature": auth.generate_signature(&now).unwrap(),
+            "timestamp": format_timestamp(&now),
+        });
+        if let Some(partner_id) = partner_id {
+            body["partner_id"] = partner_id.into();
+        }
+        body.to_string()
+    }
+
+    #[test]
+    fn tenants_share_the_transport_and_sign_with_their_own_partner_id() {
+        let transport = FakeTransport::new(JOB_STATUS_BODY);
+        let registry = registry(transport.clone());
+
+        for tenant in ["retail", "lending"] {
+            let client = registry.client(tenant).unwrap();
+            tokio_test::block_on(client.get_job_status("user-1", "job-1", None, None)).unwrap();
+        }
+
+        let partner_ids: Vec<_> = transport
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| serde_json::from_str::<serde_json::Value>(&request.body).unwrap()["partner_id"].clone())
+            .collect();
+        assert_eq!(partner_ids, ["001", "002"]);
+        assert!(matches!(registry.client("wholesale"), Err(Error::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn callbacks_are_routed_by_partner_id_or_signing_key() {
+        let registry = registry(FakeTransport::new(JOB_STATUS_BODY));
+        let lending = Auth::new("lending-key", "002");
+
+        let (by_partner_id, _) = registry
+            .handle_callback(Vec::<(&str, &str)>::new(), callback(&lending, Some("002")).as_bytes())
+            .unwrap();
+        let (by_key, _) = registry
+            .handle_callback(Vec::<(&str, &str)>::new(), callback(&lending, None).as_bytes())
+            .unwrap();
+        let mislabelled =
+            registry.handle_callback(Vec::<(&str, &str)>::new(), callback(&lending, Some("001")).as_bytes());
+
+        assert_eq!(by_partner_id, "lending");
+        assert_eq!(by_key, "lending");
+        assert!(matches!(
+            mislabelled,
+            Err(Error::SignatureVerification(VerificationFailure::InvalidSignature))
+        ));
+    }
+}
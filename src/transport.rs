@@ -0,0 +1,431 @@
+use async_trait::async_trait;
+use reqwest::{Certificate, Client, ClientBuilder, Identity, Proxy};
+use std::fmt::{self, Debug};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+
+/// The HTTP method of an [`HttpRequest`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    #[default]
+    Post,
+}
+
+impl From<Method> for reqwest::Method {
+    fn from(method: Method) -> Self {
+        match method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+        }
+    }
+}
+
+/// A fully signed request, ready to be put on the wire.
+///
+/// New fields may be added, so build one with [`new`](Self::new) rather than
+/// a struct literal.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Overrides the transport's default timeout for this request.
+    pub timeout: Option<Duration>,
+}
+
+impl HttpRequest {
+    /// A request with no headers, an empty body and the transport's timeout.
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            body: String::new(),
+            timeout: None,
+        }
+    }
+}
+
+/// The raw status and body returned for an [`HttpRequest`].
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Sends signed requests on behalf of an [`ApiClient`](crate::ApiClient).
+///
+/// Implement this to plug in a different HTTP stack, an instrumented client
+/// or an in-process fake for tests.
+#[async_trait]
+pub trait Transport: Debug + Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+/// Network settings applied to the default reqwest transports.
+#[derive(Debug, Clone, Default)]
+pub struct HttpOptions {
+    pub proxy: Option<ProxyConfig>,
+    /// Extra PEM-encoded root certificates to trust, e.g. a private CA.
+    pub root_certificates: Vec<Vec<u8>>,
+    /// A client certificate presented for mutual TLS.
+    pub identity: Option<ClientIdentity>,
+    /// Limit on establishing a connection. `Config::timeout` still bounds the whole request.
+    pub connect_timeout: Option<Duration>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub pool_idle_timeout: Option<Duration>,
+}
+
+impl HttpOptions {
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn with_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    pub fn with_identity(mut self, identity: ClientIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn with_pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max_idle);
+        self
+    }
+
+    pub fn with_pool_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    fn proxy(&self) -> Result<Option<Proxy>> {
+        let Some(config) = &self.proxy else {
+            return Ok(None);
+        };
+
+        let mut proxy = Proxy::all(&config.url)
+            .map_err(|e| Error::Config(format!("Invalid proxy URL {:?}: {}", config.url, e)))?;
+        if let Some(username) = &config.username {
+            let password = config.password.as_deref().map(String::as_str).unwrap_or_default();
+            proxy = proxy.basic_auth(username, password);
+        }
+
+        Ok(Some(proxy))
+    }
+
+    fn root_certificates(&self) -> Result<Vec<Certificate>> {
+        self.root_certificates
+            .iter()
+            .map(|pem| {
+                Certificate::from_pem(pem)
+                    .map_err(|e| Error::Config(format!("Invalid root certificate: {}", e)))
+            })
+            .collect()
+    }
+
+    fn identity(&self) -> Result<Option<Identity>> {
+        self.identity
+            .as_ref()
+            .map(|identity| {
+                Identity::from_pkcs8_pem(&identity.certificate_pem, &identity.key_pem)
+                    .map_err(|e| Error::Config(format!("Invalid client identity: {}", e)))
+            })
+            .transpose()
+    }
+}
+
+/// An HTTP(S) proxy that every request is routed through.
+#[derive(Clone)]
+pub struct ProxyConfig {
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<Zeroizing<String>>,
+}
+
+impl ProxyConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            username: None,
+            password: None,
+        }
+    }
+
+    pub fn with_basic_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self.password = Some(Zeroizing::new(password.into()));
+        self
+    }
+}
+
+impl Debug for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "[REDACTED]"))
+            .finish()
+    }
+}
+
+/// A PEM certificate chain and PKCS#8 private key used for mutual TLS.
+#[derive(Clone)]
+pub struct ClientIdentity {
+    pub certificate_pem: Vec<u8>,
+    pub key_pem: Zeroizing<Vec<u8>>,
+}
+
+impl ClientIdentity {
+    pub fn from_pkcs8_pem(certificate_pem: impl Into<Vec<u8>>, key_pem: impl Into<Vec<u8>>) -> Self {
+        Self {
+            certificate_pem: certificate_pem.into(),
+            key_pem: Zeroizing::new(key_pem.into()),
+        }
+    }
+}
+
+impl Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientIdentity")
+            .field("certificate_pem", &format_args!("{} bytes", self.certificate_pem.len()))
+            .field("key_pem", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// Applies the timeout and [`HttpOptions`] from a [`Config`] to a reqwest
+/// client builder. The async and blocking builders share method names but
+/// not a trait, hence the macro.
+macro_rules! configure_builder {
+    ($builder:expr, $config:expr) => {{
+        let config: &Config = $config;
+        let http = &config.http;
+        let mut builder = $builder.timeout(Duration::from_secs(config.timeout));
+
+        if let Some(proxy) = http.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in http.root_certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(identity) = http.identity()? {
+            builder = builder.identity(identity);
+        }
+        if let Some(connect_timeout) = http.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(max_idle) = http.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(idle_timeout) = http.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(idle_timeout);
+        }
+
+        builder
+    }};
+}
+
+/// The default transport, backed by a [`reqwest::Client`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let client = configure_builder!(ClientBuilder::new(), config)
+            .build()
+            .map_err(Error::Http)?;
+
+        Ok(Self::new(client))
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let mut builder = self.client.request(request.method.into(), &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        let response = builder
+            .body(request.body)
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        let status = response.status().as_u16();
+        let body = response.text().await.map_err(Error::Http)?;
+
+        Ok(HttpResponse { status, body })
+    }
+}
+
+#[cfg(feature = "blocking")]
+pub mod blocking {
+    use super::*;
+
+    /// Blocking counterpart of [`Transport`](super::Transport).
+    pub trait Transport: Debug + Send + Sync {
+        fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+    }
+
+    /// The default blocking transport, backed by a [`reqwest::blocking::Client`].
+    #[derive(Debug, Clone)]
+    pub struct ReqwestTransport {
+        client: reqwest::blocking::Client,
+    }
+
+    impl ReqwestTransport {
+        pub fn new(client: reqwest::blocking::Client) -> Self {
+            Self { client }
+        }
+
+        pub fn from_config(config: &Config) -> Result<Self> {
+            let client = configure_builder!(reqwest::blocking::ClientBuilder::new(), config)
+                .build()
+                .map_err(Error::Http)?;
+
+            Ok(Self::new(client))
+        }
+    }
+
+    impl Transport for ReqwestTransport {
+        fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+            let mut builder = self.client.request(request.method.into(), &request.url);
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let response = builder.body(request.body).send().map_err(Error::Http)?;
+
+            let status = response.status().as_u16();
+            let body = response.text().map_err(Error::Http)?;
+
+            Ok(HttpResponse { status, body })
+        }
+    }
+}
+
+/// An in-memory transport shared by the crate's tests.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    /// Records every request, replays queued responses in order, then
+    /// answers with `status` and `body`.
+    #[derive(Debug)]
+    pub struct FakeTransport {
+        pub requests: Mutex<Vec<HttpRequest>>,
+        responses: Mutex<VecDeque<HttpResponse>>,
+        status: u16,
+        body: String,
+    }
+
+    impl FakeTransport {
+        pub fn new(body: &str) -> Arc<Self> {
+            Self::replying(200, body)
+        }
+
+        pub fn replying(status: u16, body: &str) -> Arc<Self> {
+            Arc::new(Self {
+                requests: Mutex::new(Vec::new()),
+                responses: Mutex::new(VecDeque::new()),
+                status,
+                body: body.to_string(),
+            })
+        }
+
+        pub fn queue(self: &Arc<Self>, status: u16, body: &str) -> Arc<Self> {
+            self.responses.lock().unwrap().push_back(HttpResponse {
+                status,
+                body: body.to_string(),
+            });
+            self.clone()
+        }
+
+        pub fn urls(&self) -> Vec<String> {
+            self.requests.lock().unwrap().iter().map(|request| request.url.clone()).collect()
+        }
+
+        fn respond(&self, request: HttpRequest) -> Result<HttpResponse> {
+            self.requests.lock().unwrap().push(request);
+            let queued = self.responses.lock().unwrap().pop_front();
+            Ok(queued.unwrap_or_else(|| HttpResponse {
+                status: self.status,
+                body: self.body.clone(),
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl Transport for Arc<FakeTransport> {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+            self.respond(request)
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    impl blocking::Transport for Arc<FakeTransport> {
+        fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+            self.respond(request)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_options_are_validated_when_the_transport_is_built() {
+        let bad_proxy = Config::new("key", "partner")
+            .with_http_options(HttpOptions::default().with_proxy(ProxyConfig::new("not a url")));
+        let bad_certificate = Config::new("key", "partner")
+            .with_http_options(HttpOptions::default().with_root_certificate("not a certificate"));
+
+        assert!(matches!(ReqwestTransport::from_config(&bad_proxy), Err(Error::Config(_))));
+        assert!(matches!(ReqwestTransport::from_config(&bad_certificate), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn proxy_settings_and_pool_options_build_a_client() {
+        let http = HttpOptions::default()
+            .with_proxy(ProxyConfig::new("http://proxy.internal:3128").with_basic_auth("svc", "hunter2"))
+            .with_connect_timeout(Duration::from_secs(5))
+            .with_pool_max_idle_per_host(4)
+            .with_pool_idle_timeout(Duration::from_secs(90));
+        let config = Config::new("key", "partner").with_http_options(http);
+
+        assert!(ReqwestTransport::from_config(&config).is_ok());
+        assert!(!format!("{:?}", config.http).contains("hunter2"));
+    }
+}
@@ -2,7 +2,9 @@ use base64::{engine::general_purpose, Engine};
 use std::path::Path;
 use std::fs::File;
 use std::io::Read;
+use std::time::Duration;
 
+use crate::date_of_birth::DateOfBirth;
 use crate::error::{Error, Result};
 
 
@@ -21,6 +23,16 @@ pub fn encode_image_bytes(bytes: &[u8]) -> String {
     general_purpose::STANDARD.encode(bytes)
 }
 
-pub fn format_date(year: u16, month: u8, day: u8) -> String {
-    format!("{:04}-{:02}-{:02}", year, month, day)
+/// Formats a date the way Smile ID expects it, rejecting dates that do not
+/// exist. Prefer passing a [`DateOfBirth`] to the products directly.
+pub fn format_date(year: u16, month: u8, day: u8) -> Result<String> {
+    DateOfBirth::new(year.into(), month.into(), day.into()).map(|date| date.to_string())
+}
+
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "async")]
+    tokio::time::sleep(duration).await;
+
+    #[cfg(not(feature = "async"))]
+    std::thread::sleep(duration);
 }